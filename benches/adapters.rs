@@ -0,0 +1,123 @@
+//! Throughput of `next_point` for the adapters shipped with the crate.
+
+use ahash::AHasher;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use hash_collider::adapters::{ClosureAdapter, TruncatedDigest, TruncatedHasher};
+use hash_collider::block_hash::BlockHash;
+use hash_collider::md5::Md5;
+use hash_collider::sha1::Sha1;
+use hash_collider::sha256::Sha256;
+use hash_collider::siphash::{SipHasher13, SipHasher24};
+use hash_collider::{md5, BitPoint, HashAdapter};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+fn prefixed<H: Hasher>(mut hasher: H, prefix: u64) -> H {
+    hasher.write_u64(prefix);
+    hasher
+}
+
+/// Step `adapter` along a trail, so every call depends on the previous one.
+fn bench_adapter<A: HashAdapter<Point = u64>>(c: &mut Criterion, name: &str, adapter: A) {
+    let mut group = c.benchmark_group("next_point");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function(name, |b| {
+        let mut x = 0x0123_4567_89ab_cdef;
+        b.iter(|| {
+            x = adapter.next_point(black_box(x), adapter.bifurcation(x));
+            x
+        })
+    });
+    group.finish();
+}
+
+fn next_point(c: &mut Criterion) {
+    bench_adapter(
+        c,
+        "std",
+        TruncatedHasher::new(
+            prefixed(DefaultHasher::new(), 0),
+            prefixed(DefaultHasher::new(), 42),
+            64,
+            0x3ffff,
+        ),
+    );
+    bench_adapter(
+        c,
+        "ahash",
+        TruncatedHasher::new(
+            prefixed(AHasher::new_with_keys(1234, 5678), 0),
+            prefixed(AHasher::new_with_keys(1234, 5678), 42),
+            64,
+            0x3ffff,
+        ),
+    );
+    bench_adapter(
+        c,
+        "siphash13",
+        TruncatedHasher::new(
+            prefixed(SipHasher13::new_with_keys(1, 2), 0),
+            prefixed(SipHasher13::new_with_keys(1, 2), 42),
+            64,
+            0x3ffff,
+        ),
+    );
+    bench_adapter(
+        c,
+        "siphash24",
+        TruncatedHasher::new(
+            prefixed(SipHasher24::new_with_keys(1, 2), 0),
+            prefixed(SipHasher24::new_with_keys(1, 2), 42),
+            64,
+            0x3ffff,
+        ),
+    );
+    bench_adapter(
+        c,
+        "closure_std",
+        ClosureAdapter::builder(64, |x, bi| {
+            let mut hasher = prefixed(DefaultHasher::new(), bi as u64);
+            hasher.write_u64(x);
+            hasher.finish()
+        })
+        .build(),
+    );
+
+    let mut group = c.benchmark_group("next_point");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("md5_compress", |b| {
+        let mut ihv = md5::S0;
+        let mut data = [0_u32; 16];
+        b.iter(|| {
+            data[13..].copy_from_slice(&ihv[..3]);
+            ihv = md5::S0;
+            md5::compress(&mut ihv, black_box(&data));
+            ihv
+        })
+    });
+    group.finish();
+}
+
+/// `TruncatedDigest` with 96-bit points over the hash `H`.
+fn bench_digest<H: BlockHash>(c: &mut Criterion, name: &str) {
+    let adapter = TruncatedDigest::<H, 96>::new(b"a", b"b");
+    let mut group = c.benchmark_group("next_point");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function(name, |b| {
+        let mut x = BitPoint::new(0x0123_4567_89ab_cdef);
+        b.iter(|| {
+            x = adapter.next_point(black_box(x), adapter.bifurcation(x));
+            x
+        })
+    });
+    group.finish();
+}
+
+fn digests(c: &mut Criterion) {
+    bench_digest::<Md5>(c, "md5_ihv_96");
+    bench_digest::<Sha1>(c, "sha1_96");
+    bench_digest::<Sha256>(c, "sha256_96");
+}
+
+criterion_group!(benches, next_point, digests);
+criterion_main!(benches);
@@ -0,0 +1,148 @@
+//! Cost of the collider machinery around the adapter: walking trails,
+//! the trail table, and tracing collisions.
+//!
+//! Needs the worker steps of the `bench` feature:
+//! `cargo bench --features bench --bench collider`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use hash_collider::{trace_collision, Collider, HashAdapter, TrailInfo};
+use rand::{Rng, RngCore};
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+/// A nearly free "hash", so the measurements show the collider overhead.
+struct Toy {
+    trail_bits: u32,
+}
+
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+impl HashAdapter for Toy {
+    type Point = u64;
+
+    fn trail_limit(&self) -> u64 {
+        (1 << self.trail_bits) * 20
+    }
+
+    fn make_point<R: Rng>(&self, rng: &mut R) -> Self::Point {
+        rng.next_u64()
+    }
+
+    fn is_distinguishing(&self, x: Self::Point) -> bool {
+        x & !(!0 << self.trail_bits) == 0
+    }
+
+    fn bifurcation(&self, x: Self::Point) -> bool {
+        x & 1 != 0
+    }
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        mix(x ^ bi as u64)
+    }
+
+    fn report_collision(&self, _a: Self::Point, _b: Self::Point) -> ControlFlow<(), ()> {
+        ControlFlow::Continue(())
+    }
+}
+
+fn walk_trails(c: &mut Criterion) {
+    let mut group = c.benchmark_group("worker");
+    for trail_bits in [4, 8, 12] {
+        let collider = Collider::new(Toy { trail_bits });
+        let mut rng = rand::thread_rng();
+        group.throughput(Throughput::Elements(1 << trail_bits));
+        group.bench_function(format!("trail/{}", trail_bits), |b| {
+            b.iter(|| {
+                let start = rng.next_u64();
+                if let Some((end, length)) = collider.bench_walk_trail(start) {
+                    collider.bench_add_trail(start, end, length);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// A collider holding `size` trails with the ends `0..size`.
+fn filled_collider(size: u64) -> Collider<Toy> {
+    let collider = Collider::new(Toy { trail_bits: 8 });
+    for end in 0..size {
+        collider.bench_add_trail(mix(end), end, 1);
+    }
+    collider
+}
+
+fn trail_table(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trail_table");
+    group.sample_size(20);
+    for size in [10_000, 100_000, 1_000_000] {
+        group.bench_function(format!("insert/{}", size), |b| {
+            b.iter_custom(|iters| {
+                let collider = filled_collider(size);
+                let start = Instant::now();
+                for ii in 0..iters {
+                    let end = size + ii;
+                    collider.bench_add_trail(mix(end), black_box(end), 1);
+                }
+                start.elapsed()
+            })
+        });
+        // Adding the same trail again is a lookup plus a trivial trace.  The
+        // lists per end grow by one every `size` iterations, which is noise
+        // for these table sizes.
+        group.bench_function(format!("lookup/{}", size), |b| {
+            b.iter_custom(|iters| {
+                let collider = filled_collider(size);
+                let start = Instant::now();
+                for ii in 0..iters {
+                    let end = ii % size;
+                    collider.bench_add_trail(mix(end), black_box(end), 1);
+                }
+                start.elapsed()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn trace_trails(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trace_collision");
+    for trail_bits in [8, 12] {
+        let adapter = Toy { trail_bits };
+        let mut rng = rand::thread_rng();
+        // Find a trail long enough to start a second one from its middle.
+        let (start, length) = loop {
+            let collider = Collider::new(Toy { trail_bits });
+            let start = rng.next_u64();
+            if let Some((_end, length)) = collider.bench_walk_trail(start) {
+                if length >= 1 << trail_bits {
+                    break (start, length);
+                }
+            }
+        };
+        let middle =
+            (0..length / 2).fold(start, |x, _| adapter.next_point(x, adapter.bifurcation(x)));
+
+        let a = TrailInfo { start, length };
+        let b = TrailInfo {
+            start: middle,
+            length: length - length / 2,
+        };
+        group.throughput(Throughput::Elements(length));
+        group.bench_function(format!("robin_hood/{}", trail_bits), |bench| {
+            bench.iter(|| trace_collision(&adapter, black_box(&a), black_box(&b)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().measurement_time(Duration::from_secs(3));
+    targets = walk_trails, trail_table, trace_trails
+}
+criterion_main!(benches);
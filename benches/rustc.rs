@@ -0,0 +1,52 @@
+//! Throughput of `next_point` for the rustc adapters, each step hashing a
+//! new item name into a `DefPathHash` and what is built on top of it.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use hash_collider::rustc::{
+    CrateConfig, CrateIdAdapter, DefPathAdapter, ItemKind, StructTypeIdAdapter, SymbolHashAdapter,
+    RUSTC_1_64, RUSTC_1_72, RUSTC_1_95,
+};
+use hash_collider::HashAdapter;
+
+/// Step `adapter` along a trail, so every call depends on the previous one.
+fn bench_adapter<A: HashAdapter<Point = u64>>(c: &mut Criterion, name: &str, adapter: A) {
+    let mut group = c.benchmark_group("next_point");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function(name, |b| {
+        let mut x = 0x0123_4567_89ab_cdef;
+        b.iter(|| {
+            x = adapter.next_point(black_box(x), adapter.bifurcation(x));
+            x
+        })
+    });
+    group.finish();
+}
+
+fn next_point(c: &mut Criterion) {
+    let metadata = vec!["051dac071847dbb3".to_owned()];
+
+    let crate_id = RUSTC_1_72.crate_id("playground", true, metadata.clone());
+    let root = RUSTC_1_72.snippet_root(crate_id);
+    let adapter = DefPathAdapter::new(&root, (ItemKind::Mod, "foo_"), (ItemKind::Mod, "bar_"));
+    bench_adapter(c, "rustc_def_path", adapter);
+
+    let crate_id = RUSTC_1_64.crate_id("playground", true, metadata.clone());
+    let main = RUSTC_1_64.snippet_root(crate_id);
+    let adapter = StructTypeIdAdapter::new(&main, ("Foo", "x"), ("Bar", "x")).unwrap();
+    bench_adapter(c, "rustc_type_id", adapter);
+
+    let crate_id = RUSTC_1_95.crate_id("symtest", false, metadata);
+    let root = RUSTC_1_95.snippet_root(crate_id);
+    let adapter = SymbolHashAdapter::new("symtest", &root, "foo_", "bar_").unwrap();
+    bench_adapter(c, "rustc_symbol", adapter);
+
+    let adapter = CrateIdAdapter::new(
+        &RUSTC_1_95,
+        CrateConfig::new("playground", true, "a"),
+        CrateConfig::new("playground", true, "b"),
+    );
+    bench_adapter(c, "rustc_crate_id", adapter);
+}
+
+criterion_group!(benches, next_point);
+criterion_main!(benches);
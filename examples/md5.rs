@@ -0,0 +1,27 @@
+//! demonstrating 96-bit collision on md5
+//!
+//! usage: md5 [prefix_a] [prefix_b] [out_dir]
+//!
+//! Colliding message pairs are written to `out_dir`, `md5-collisions` by default.
+
+use core::ops::ControlFlow;
+use hash_collider::adapters::Md5Ihv;
+use hash_collider::{stat_printer, Collider};
+
+const TRAIL_BITS: u32 = 20;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let prefix_a = args.next().unwrap_or_else(|| "a".to_owned());
+    let prefix_b = args.next().unwrap_or_else(|| "b".to_owned());
+    let out_dir = args.next().unwrap_or_else(|| "md5-collisions".to_owned());
+    let adapter = Md5Ihv::<96>::new(prefix_a.as_bytes(), prefix_b.as_bytes())
+        .with_trail_bits(TRAIL_BITS)
+        .with_out_dir(out_dir);
+    let (ihv_a, ihv_b) = adapter.midstates();
+    println!("ihv_a={:08x?} ihv_b={:08x?}", ihv_a, ihv_b);
+
+    let mut collider = Collider::new(adapter);
+    let thread_count = num_cpus::get();
+    collider.run(thread_count, stat_printer(1, 96, ControlFlow::Continue(())));
+}
@@ -0,0 +1,24 @@
+use core::ops::ControlFlow;
+use hash_collider::rustc::{Profile, ProofCrate, StructTypeIdAdapter, RUSTC_1_64};
+use hash_collider::{stat_printer, Collider};
+
+const PROFILE: &Profile = &RUSTC_1_64;
+const PROOF_DIR: &str = "target/type-id-proofs";
+/// Set to build and run the proofs with the local rustc.
+const VERIFY_ENV: &str = "VERIFY_PROOFS";
+
+fn main() {
+    let metadata = vec!["a0ecb98bfb1b38c8".to_owned()];
+    let crate_id = PROFILE.crate_id("playground", true, metadata.clone());
+    let proof = ProofCrate::new("playground", metadata, PROFILE.playground_wrapper);
+    let mut hash =
+        StructTypeIdAdapter::new(&PROFILE.snippet_root(crate_id), ("Foo", "x"), ("Bar", "x"))
+            .unwrap()
+            .with_proof_dir(PROOF_DIR, proof, &[])
+            .unwrap();
+    if std::env::var_os(VERIFY_ENV).is_some() {
+        hash = hash.with_proof_verification();
+    }
+    let thread_count = num_cpus::get();
+    Collider::new(hash).run(thread_count, stat_printer(1, 64, ControlFlow::Continue(())));
+}
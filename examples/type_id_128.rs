@@ -0,0 +1,25 @@
+//! Colliding modules in the playground: two modules with the same
+//! `DefPathHash` make the `TypeId`s of identical structs inside them equal.
+
+use core::ops::ControlFlow;
+use hash_collider::rustc::{DefPathAdapter, ItemKind, Profile, RUSTC_1_72};
+use hash_collider::{stat_printer, Collider};
+
+const PROFILE: &Profile = &RUSTC_1_72;
+const DEFAULT_TRAIL_MASK: u64 = 0x3ffff;
+
+fn main() {
+    let crate_id = PROFILE.crate_id("playground", true, vec!["051dac071847dbb3".to_owned()]);
+    let parent = PROFILE.snippet_root(crate_id);
+    println!("crate id: {:016x}, parent: {}", crate_id.hash, parent);
+    let adapter = DefPathAdapter::new(&parent, (ItemKind::Mod, "foo_"), (ItemKind::Mod, "bar_"))
+        .with_bits(64, DEFAULT_TRAIL_MASK);
+    let mut collider = Collider::new(adapter);
+    let thread_count = num_cpus::get();
+    collider.run(thread_count, stat_printer(1, 64, ControlFlow::Continue(())));
+}
+
+// {"rustc_fingerprint":8333503651157263990,
+// "outputs":{"4614504638168534921":{"success":true,"status":"","code":0,"stdout":
+// "rustc 1.72.0-nightly (3b2073f07 2023-06-17)\nbinary: rustc\ncommit-hash: 3b2073f0762cff4d3d625bb10017e0ce4e7abe50\ncommit-date: 2023-06-17\nhost: x86_64-unknown-linux-gnu\nrelease: 1.72.0-nightly\nLLVM version: 16.0.5\n","stderr":""},"15729799797837862367":{"success":true,"status":"","code":0,"stdout":"___\nlib___.rlib\nlib___.so\nlib___.so\nlib___.a\nlib___.so\n/playground/.rustup/toolchains/nightly-x86_64-unknown-linux-gnu\noff\npacked\nunpacked\n___\ndebug_assertions\noverflow_checks\npanic=\"unwind\"\nproc_macro\ntarget_abi=\"\"\ntarget_arch=\"x86_64\"\ntarget_endian=\"little\"\ntarget_env=\"gnu\"\ntarget_family=\"unix\"\ntarget_feature=\"fxsr\"\ntarget_feature=\"sse\"\ntarget_feature=\"sse2\"\ntarget_has_atomic\ntarget_has_atomic=\"16\"\ntarget_has_atomic=\"32\"\ntarget_has_atomic=\"64\"\ntarget_has_atomic=\"8\"\ntarget_has_atomic=\"ptr\"\ntarget_has_atomic_equal_alignment=\"16\"\ntarget_has_atomic_equal_alignment=\"32\"\ntarget_has_atomic_equal_alignment=\"64\"\ntarget_has_atomic_equal_alignment=\"8\"\ntarget_has_atomic_equal_alignment=\"ptr\"\ntarget_has_atomic_load_store\ntarget_has_atomic_load_store=\"16\"\ntarget_has_atomic_load_store=\"32\"\ntarget_has_atomic_load_store=\"64\"\ntarget_has_atomic_load_store=\"8\"\ntarget_has_atomic_load_store=\"ptr\"\ntarget_os=\"linux\"\ntarget_pointer_width=\"64\"\ntarget_thread_local\ntarget_vendor=\"unknown\"\nunix\n","stderr":""}},"successes":{}}target/release/deps/playground-051dac071847dbb3
+// target/release/deps/playground-051dac071847dbb3.d
@@ -0,0 +1,184 @@
+use crate::adapters::{default_trail_bits, low_mask, TruncatedPoint};
+use crate::HashAdapter;
+use core::ops::ControlFlow;
+use rand::{Rng, RngCore};
+
+type MakePointFn = Box<dyn Fn(&mut dyn RngCore) -> u64 + Send + Sync>;
+type NextPointFn = Box<dyn Fn(u64, bool) -> u64 + Send + Sync>;
+type PredicateFn = Box<dyn Fn(u64) -> bool + Send + Sync>;
+type ReportFn = Box<dyn Fn(u64, u64) -> ControlFlow<(), ()> + Send + Sync>;
+
+/// A `HashAdapter` over `u64` points assembled from closures.
+///
+/// Built with `ClosureAdapter::builder`.  The output of `next_point` is
+/// truncated to the configured width, so the closure may return the full
+/// hash value.
+pub struct ClosureAdapter {
+    point: TruncatedPoint,
+    make_point: MakePointFn,
+    next_point: NextPointFn,
+    bifurcation: PredicateFn,
+    is_distinguishing: PredicateFn,
+    report_collision: ReportFn,
+}
+
+impl ClosureAdapter {
+    /// Start building an adapter for `bits`-wide points stepped by `next_point`.
+    pub fn builder<F>(bits: u32, next_point: F) -> ClosureAdapterBuilder
+    where
+        F: Fn(u64, bool) -> u64 + Send + Sync + 'static,
+    {
+        assert!(
+            (1..=64).contains(&bits),
+            "point width must be in 1..=64 bits"
+        );
+        ClosureAdapterBuilder {
+            bits,
+            trail_bits: default_trail_bits(bits),
+            next_point: Box::new(next_point),
+            make_point: None,
+            bifurcation: None,
+            is_distinguishing: None,
+            report_collision: None,
+        }
+    }
+}
+
+pub struct ClosureAdapterBuilder {
+    bits: u32,
+    trail_bits: u32,
+    next_point: NextPointFn,
+    make_point: Option<MakePointFn>,
+    bifurcation: Option<PredicateFn>,
+    is_distinguishing: Option<PredicateFn>,
+    report_collision: Option<ReportFn>,
+}
+
+impl ClosureAdapterBuilder {
+    /// Expected trail length is `2^trail_bits`.  By default a point is
+    /// distinguishing when its lowest `trail_bits` bits are zero.
+    pub fn trail_bits(mut self, trail_bits: u32) -> Self {
+        assert!(
+            trail_bits <= self.bits,
+            "distinguishing bits do not fit into the point"
+        );
+        self.trail_bits = trail_bits;
+        self
+    }
+
+    /// Defaults to uniformly random `bits`-wide points.
+    pub fn make_point<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&mut dyn RngCore) -> u64 + Send + Sync + 'static,
+    {
+        self.make_point = Some(Box::new(f));
+        self
+    }
+
+    /// Defaults to the lowest bit of the point.
+    pub fn bifurcation<F>(mut self, f: F) -> Self
+    where
+        F: Fn(u64) -> bool + Send + Sync + 'static,
+    {
+        self.bifurcation = Some(Box::new(f));
+        self
+    }
+
+    /// Defaults to the lowest `trail_bits` bits being zero.
+    pub fn is_distinguishing<F>(mut self, f: F) -> Self
+    where
+        F: Fn(u64) -> bool + Send + Sync + 'static,
+    {
+        self.is_distinguishing = Some(Box::new(f));
+        self
+    }
+
+    /// Defaults to printing both points and continuing the search.
+    pub fn report_collision<F>(mut self, f: F) -> Self
+    where
+        F: Fn(u64, u64) -> ControlFlow<(), ()> + Send + Sync + 'static,
+    {
+        self.report_collision = Some(Box::new(f));
+        self
+    }
+
+    pub fn build(self) -> ClosureAdapter {
+        let point = TruncatedPoint::new(self.bits, low_mask(self.trail_bits));
+        ClosureAdapter {
+            point,
+            make_point: self
+                .make_point
+                .unwrap_or_else(|| Box::new(move |mut rng| point.make_point(&mut rng))),
+            next_point: self.next_point,
+            bifurcation: self
+                .bifurcation
+                .unwrap_or_else(|| Box::new(move |x| point.bifurcation(x))),
+            is_distinguishing: self
+                .is_distinguishing
+                .unwrap_or_else(|| Box::new(move |x| point.is_distinguishing(x))),
+            report_collision: self.report_collision.unwrap_or_else(|| {
+                Box::new(|a, b| {
+                    println!("found collision! {:x?} {:x?}", a, b);
+                    ControlFlow::Continue(())
+                })
+            }),
+        }
+    }
+}
+
+impl HashAdapter for ClosureAdapter {
+    type Point = u64;
+
+    fn trail_limit(&self) -> u64 {
+        self.point.trail_limit()
+    }
+
+    fn make_point<R: Rng>(&self, rng: &mut R) -> Self::Point {
+        (self.make_point)(rng)
+    }
+
+    fn is_distinguishing(&self, x: Self::Point) -> bool {
+        (self.is_distinguishing)(x)
+    }
+
+    fn bifurcation(&self, x: Self::Point) -> bool {
+        (self.bifurcation)(x)
+    }
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.point.truncate((self.next_point)(x, bi))
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        (self.report_collision)(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClosureAdapter;
+    use crate::{stat_printer, Collider};
+    use core::ops::ControlFlow;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    #[test]
+    fn collides_with_defaults() {
+        fn my_hash(prefix: u64, x: u64) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            hasher.write_u64(prefix);
+            hasher.write_u64(x);
+            hasher.finish()
+        }
+
+        let adapter = ClosureAdapter::builder(32, |x, bi| my_hash(bi as u64, x))
+            .report_collision(|a, b| {
+                assert_eq!(my_hash(1, a) as u32, my_hash(0, b) as u32);
+                ControlFlow::Break(())
+            })
+            .build();
+        let mut collider = Collider::new(adapter);
+        collider.run(2, stat_printer(1, 32, ControlFlow::Break(())));
+        assert!(collider.report_stats().collisions > 0);
+    }
+}
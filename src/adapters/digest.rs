@@ -0,0 +1,306 @@
+use crate::adapters::default_trail_bits;
+use crate::block_hash::{compress_blocks, digest, BlockHash, BLOCK_SIZE};
+use crate::md5::Md5;
+use crate::printer::hex;
+use crate::{BitPoint, HashAdapter, Reporter};
+use core::marker::PhantomData;
+use core::ops::ControlFlow;
+use rand::Rng;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Collides the first `BITS` bits of `H(pad(prefix_a) || x)` with
+/// `H(pad(prefix_b) || y)`, where `x` and `y` are `BITS / 8` bytes long.
+///
+/// Each prefix is padded with zeros to a block boundary and compressed once,
+/// the resulting midstates are the starting states of every step.  A step
+/// compresses a single block holding the point followed by the standard
+/// padding, so every point is the truncated digest of a real message, and
+/// found collisions can be checked with the usual command line tools.
+///
+/// `BITS` must be a multiple of 8 in `8..=128`, the intended widths are 64,
+/// 80 and 96.  A point is distinguishing when its top `trail_bits` bits are zero.
+///
+/// With `with_out_dir`, both colliding messages are written to files, named
+/// by the colliding digest prefix and their points, as they are found.
+pub struct TruncatedDigest<H: BlockHash, const BITS: usize> {
+    prefix_a: Vec<u8>,
+    prefix_b: Vec<u8>,
+    state_a: H::State,
+    state_b: H::State,
+    tail_a: [u8; BLOCK_SIZE],
+    tail_b: [u8; BLOCK_SIZE],
+    trail_bits: u32,
+    out_dir: Option<PathBuf>,
+    reporter: Reporter,
+    _hash: PhantomData<fn() -> H>,
+}
+
+/// Truncated MD5, whose midstates are the intermediate hash values (IHVs).
+///
+/// Unlike the original `examples/md5`, the point is not put into
+/// `data[13..]` of a block of zeros followed by a padding block.  Only
+/// `BITS` of the 128 IHV bits collide, and compressing the padding block
+/// would mix the other bits back in, so those messages would have different
+/// MD5 sums.  The point starts the last block instead, and the padding
+/// follows it in the same block, so the colliding IHVs are the truncated
+/// MD5 sums of the messages.
+pub type Md5Ihv<const BITS: usize> = TruncatedDigest<Md5, BITS>;
+
+/// Pad `prefix` with zeros to a whole number of blocks.
+pub fn pad_to_block(prefix: &[u8]) -> Vec<u8> {
+    let mut padded = prefix.to_vec();
+    padded.resize(prefix.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE, 0);
+    padded
+}
+
+impl<H: BlockHash, const BITS: usize> TruncatedDigest<H, BITS> {
+    const VALID_WIDTH: () = assert!(
+        BITS.is_multiple_of(8) && BITS >= 8 && BITS <= 128,
+        "TruncatedDigest width must be a multiple of 8 in 8..=128"
+    );
+
+    /// Number of message bytes in a point.
+    pub const BYTES: usize = BITS / 8;
+
+    pub fn new(prefix_a: &[u8], prefix_b: &[u8]) -> Self {
+        let () = Self::VALID_WIDTH;
+        let prefix_a = pad_to_block(prefix_a);
+        let prefix_b = pad_to_block(prefix_b);
+        let mut state_a = H::INIT;
+        compress_blocks::<H>(&mut state_a, &prefix_a);
+        let mut state_b = H::INIT;
+        compress_blocks::<H>(&mut state_b, &prefix_b);
+        Self {
+            tail_a: Self::tail(prefix_a.len()),
+            tail_b: Self::tail(prefix_b.len()),
+            prefix_a,
+            prefix_b,
+            state_a,
+            state_b,
+            trail_bits: default_trail_bits(BITS as u32),
+            out_dir: None,
+            reporter: Reporter::default(),
+            _hash: PhantomData,
+        }
+    }
+
+    /// The last block without the point: zeros where the point goes, then the
+    /// padding for a message of `prefix_len + BYTES` bytes.
+    fn tail(prefix_len: usize) -> [u8; BLOCK_SIZE] {
+        let mut block = [0_u8; BLOCK_SIZE];
+        block[Self::BYTES..].copy_from_slice(&H::padding((prefix_len + Self::BYTES) as u64));
+        block
+    }
+
+    pub fn with_trail_bits(mut self, trail_bits: u32) -> Self {
+        assert!(
+            trail_bits as usize <= BITS,
+            "distinguishing bits do not fit into the point"
+        );
+        self.trail_bits = trail_bits;
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Write colliding message pairs into `out_dir`, which is created if needed.
+    pub fn with_out_dir<P: Into<PathBuf>>(mut self, out_dir: P) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// The padded prefixes the points are appended to.
+    pub fn prefixes(&self) -> (&[u8], &[u8]) {
+        (&self.prefix_a, &self.prefix_b)
+    }
+
+    /// Hash states after the padded prefixes.
+    pub fn midstates(&self) -> (H::State, H::State) {
+        (self.state_a, self.state_b)
+    }
+
+    /// Truncated digest of the message ending in `x`, where the prefix is
+    /// chosen by `bi`.
+    #[inline]
+    pub fn hash(&self, x: BitPoint<BITS>, bi: bool) -> BitPoint<BITS> {
+        let (mut state, mut block) = if bi {
+            (self.state_a, self.tail_a)
+        } else {
+            (self.state_b, self.tail_b)
+        };
+        block[..Self::BYTES].copy_from_slice(&x.to_le_bytes()[..Self::BYTES]);
+        H::compress(&mut state, &block);
+        BitPoint::from_le_bytes(&H::output_prefix(&state))
+    }
+
+    /// The complete message hashed for `x`: the padded prefix chosen by `bi`
+    /// followed by the point bytes.  Its digest starts with `hash(x, bi)`.
+    pub fn message(&self, x: BitPoint<BITS>, bi: bool) -> Vec<u8> {
+        let mut message = if bi {
+            self.prefix_a.clone()
+        } else {
+            self.prefix_b.clone()
+        };
+        message.extend_from_slice(&x.to_le_bytes()[..Self::BYTES]);
+        message
+    }
+
+    /// Write the messages of the collision `(a, b)` into `dir` as
+    /// `<digest>-<a>-a.bin` and `<digest>-<b>-b.bin`, with the points in
+    /// hex, so collisions with the same digest prefix do not overwrite each
+    /// other.
+    pub fn write_messages(
+        &self,
+        dir: &Path,
+        a: BitPoint<BITS>,
+        b: BitPoint<BITS>,
+    ) -> io::Result<(PathBuf, PathBuf)> {
+        let name = hex(&digest::<H>(&self.message(a, true))[..Self::BYTES]);
+        let point = |x: BitPoint<BITS>| hex(&x.to_le_bytes()[..Self::BYTES]);
+        std::fs::create_dir_all(dir)?;
+        let path_a = dir.join(format!("{}-{}-a.bin", name, point(a)));
+        let path_b = dir.join(format!("{}-{}-b.bin", name, point(b)));
+        std::fs::write(&path_a, self.message(a, true))?;
+        std::fs::write(&path_b, self.message(b, false))?;
+        Ok((path_a, path_b))
+    }
+}
+
+impl<H: BlockHash, const BITS: usize> HashAdapter for TruncatedDigest<H, BITS> {
+    type Point = BitPoint<BITS>;
+
+    fn trail_limit(&self) -> u64 {
+        BitPoint::<BITS>::trail_limit(self.trail_bits)
+    }
+
+    fn make_point<R: Rng>(&self, rng: &mut R) -> Self::Point {
+        BitPoint::random(rng)
+    }
+
+    fn is_distinguishing(&self, x: Self::Point) -> bool {
+        x.is_distinguishing(self.trail_bits)
+    }
+
+    fn bifurcation(&self, x: Self::Point) -> bool {
+        x.bifurcation()
+    }
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.hash(x, bi)
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        let (message_a, message_b) = (self.message(a, true), self.message(b, false));
+        let digest_a = hex(&digest::<H>(&message_a)[..Self::BYTES]);
+        let digest_b = hex(&digest::<H>(&message_b)[..Self::BYTES]);
+        assert_eq!(digest_a, digest_b);
+
+        let mut files = String::new();
+        if let Some(dir) = &self.out_dir {
+            match self.write_messages(dir, a, b) {
+                Ok((path_a, path_b)) => {
+                    files = format!(" files {} {}", path_a.display(), path_b.display())
+                }
+                Err(e) => eprintln!("failed to write colliding messages: {}", e),
+            }
+        }
+        self.reporter.report_bytes(
+            &message_a,
+            &message_b,
+            format_args!(
+                "found collision! (a, {:?}) (b, {:?}) {}={}..{}",
+                a,
+                b,
+                H::NAME,
+                digest_a,
+                files
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TruncatedDigest;
+    use crate::block_hash::{digest, BlockHash};
+    use crate::md5::Md5;
+    use crate::sha1::Sha1;
+    use crate::sha256::Sha256;
+    use crate::BitPoint;
+    use std::collections::HashMap;
+
+    #[test]
+    fn points_are_digests() {
+        fn check<H: BlockHash, const BITS: usize>(prefix_a: &[u8], prefix_b: &[u8]) {
+            let adapter = TruncatedDigest::<H, BITS>::new(prefix_a, prefix_b);
+            let (padded_a, padded_b) = adapter.prefixes();
+            assert_eq!(padded_a.len() % 64, 0);
+            assert_eq!(&padded_a[..prefix_a.len()], prefix_a);
+            let x = BitPoint::<BITS>::new(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+            for (bi, padded) in [(true, padded_a), (false, padded_b)] {
+                let message = adapter.message(x, bi);
+                assert_eq!(message.len(), padded.len() + BITS / 8);
+                assert_eq!(&message[..padded.len()], padded);
+                assert_eq!(
+                    adapter.hash(x, bi),
+                    BitPoint::from_le_bytes(&digest::<H>(&message))
+                );
+            }
+        }
+        check::<Md5, 64>(b"", b"hello");
+        check::<Md5, 80>(&[0x55; 64], &[0xaa; 100]);
+        check::<Md5, 96>(b"prefix a", b"prefix b");
+        check::<Md5, 128>(b"a", b"b");
+        check::<Sha1, 64>(b"", b"hello");
+        check::<Sha1, 96>(&[0x55; 64], &[0xaa; 100]);
+        check::<Sha256, 80>(b"prefix a", b"prefix b");
+        check::<Sha256, 128>(b"a", b"b");
+    }
+
+    #[test]
+    fn writes_colliding_messages() {
+        let adapter = TruncatedDigest::<Md5, 16>::new(b"prefix a", b"prefix b");
+        // Birthday search over the first 2^10 points of each side.
+        let hash = |x, bi| adapter.hash(BitPoint::new(x), bi);
+        let seen = (0..1 << 10)
+            .map(|x| (hash(x, true), x))
+            .collect::<HashMap<_, _>>();
+        let (digest_b, a, b) = (0..1 << 10)
+            .map(|x| (hash(x, false), x))
+            .find_map(|(hash, b)| Some((hash, *seen.get(&hash)?, b)))
+            .expect("no 16-bit collision among 2^10 points");
+        let (a, b) = (BitPoint::new(a), BitPoint::new(b));
+
+        let dir = std::env::temp_dir().join(format!("digest-messages-{}", std::process::id()));
+        let written = adapter.write_messages(&dir, a, b);
+        let files = written.as_ref().map(|(path_a, path_b)| {
+            (
+                path_a.file_name().unwrap().to_owned(),
+                std::fs::read(path_a).unwrap(),
+                std::fs::read(path_b).unwrap(),
+            )
+        });
+        std::fs::remove_dir_all(&dir).unwrap();
+        let (name_a, message_a, message_b) = files.unwrap();
+
+        assert_eq!(message_a, adapter.message(a, true));
+        assert_eq!(message_b, adapter.message(b, false));
+        for message in [&message_a, &message_b] {
+            assert_eq!(BitPoint::from_le_bytes(&digest::<Md5>(message)), digest_b);
+        }
+        let digest_a = digest::<Md5>(&message_a);
+        let point_a = a.to_le_bytes();
+        assert_eq!(
+            name_a.to_str().unwrap(),
+            format!(
+                "{:02x}{:02x}-{:02x}{:02x}-a.bin",
+                digest_a[0], digest_a[1], point_a[0], point_a[1]
+            )
+        );
+    }
+}
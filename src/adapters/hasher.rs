@@ -0,0 +1,137 @@
+use crate::adapters::{truncated_point_methods, TruncatedPoint};
+use crate::{HashAdapter, Reporter};
+use core::hash::Hasher;
+use core::ops::ControlFlow;
+
+/// Collides `H(prefix_a || x)` with `H(prefix_b || y)` for any `Hasher`,
+/// looking only at the lowest `bits` bits of the output.
+///
+/// The prefixes are given as already seeded hasher states, which are cloned
+/// for every step, so the prefix is only hashed once.
+pub struct TruncatedHasher<H> {
+    prefix_a: H,
+    prefix_b: H,
+    point: TruncatedPoint,
+    reporter: Reporter,
+}
+
+impl<H: Hasher + Clone + Sync> TruncatedHasher<H> {
+    /// `bits` is the width of the point (1..=64), a point is distinguishing
+    /// when all bits in `trail_mask` are zero.
+    pub fn new(prefix_a: H, prefix_b: H, bits: u32, trail_mask: u64) -> Self {
+        Self {
+            prefix_a,
+            prefix_b,
+            point: TruncatedPoint::new(bits, trail_mask),
+            reporter: Reporter::default(),
+        }
+    }
+
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.point.bits()
+    }
+
+    /// Truncated hash of `prefix || x`, where the prefix is chosen by `bi`.
+    pub fn hash(&self, x: u64, bi: bool) -> u64 {
+        let mut hasher = if bi {
+            self.prefix_a.clone()
+        } else {
+            self.prefix_b.clone()
+        };
+        hasher.write_u64(x);
+        self.point.truncate(hasher.finish())
+    }
+}
+
+impl<H: Hasher + Clone + Sync> HashAdapter for TruncatedHasher<H> {
+    type Point = u64;
+
+    truncated_point_methods!();
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.hash(x, bi)
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        let hash = self.hash(a, true);
+        assert_eq!(hash, self.hash(b, false));
+        self.reporter.report(
+            &a,
+            &b,
+            format_args!(
+                "found collision! (a, {:x?}) (b, {:x?}) hash={:x?}",
+                a, b, hash
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TruncatedHasher;
+    use crate::HashAdapter;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    fn seeded(prefix: &[u8]) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(prefix);
+        hasher
+    }
+
+    #[test]
+    fn truncates_prefixed_hash() {
+        let adapter = TruncatedHasher::new(seeded(b"a"), seeded(b"b"), 20, 0xff);
+        assert_eq!(adapter.bits(), 20);
+        for x in [0, 1, 0xc0ffee, u64::MAX] {
+            for (bi, prefix) in [(true, b"a"), (false, b"b")] {
+                let mut hasher = DefaultHasher::new();
+                hasher.write(prefix);
+                hasher.write_u64(x);
+                assert_eq!(adapter.next_point(x, bi), hasher.finish() & 0xf_ffff);
+            }
+        }
+        assert_ne!(adapter.next_point(7, true), adapter.next_point(7, false));
+
+        let full = TruncatedHasher::new(seeded(b"a"), seeded(b"b"), 64, 0);
+        let mut hasher = seeded(b"a");
+        hasher.write_u64(7);
+        assert_eq!(full.next_point(7, true), hasher.finish());
+    }
+
+    #[test]
+    fn distinguishing_mask() {
+        let adapter = TruncatedHasher::new(seeded(b"a"), seeded(b"b"), 16, 0xf0);
+        assert!(adapter.is_distinguishing(0x100f));
+        assert!(!adapter.is_distinguishing(0x0010));
+        assert_eq!(adapter.trail_limit(), 16 * 20);
+        assert!(adapter.bifurcation(1) && !adapter.bifurcation(2));
+
+        let mut rng = rand::thread_rng();
+        assert!((0..100).all(|_| adapter.make_point(&mut rng) <= 0xffff));
+    }
+
+    #[test]
+    #[should_panic(expected = "point width must be in 1..=64 bits")]
+    fn rejects_zero_bits() {
+        TruncatedHasher::new(seeded(b"a"), seeded(b"b"), 0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "point width must be in 1..=64 bits")]
+    fn rejects_wide_points() {
+        TruncatedHasher::new(seeded(b"a"), seeded(b"b"), 65, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "distinguishing mask does not fit into the point")]
+    fn rejects_wide_mask() {
+        TruncatedHasher::new(seeded(b"a"), seeded(b"b"), 8, 0x100);
+    }
+}
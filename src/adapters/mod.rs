@@ -0,0 +1,125 @@
+//! Ready-made `HashAdapter` implementations.
+
+mod closure;
+mod digest;
+mod hasher;
+
+pub use self::closure::{ClosureAdapter, ClosureAdapterBuilder};
+pub use self::digest::{pad_to_block, Md5Ihv, TruncatedDigest};
+pub use self::hasher::TruncatedHasher;
+
+use rand::Rng;
+
+/// Number of low zero bits that make a `bits`-wide point distinguishing,
+/// used when an adapter is not told otherwise.
+pub fn default_trail_bits(bits: u32) -> u32 {
+    (bits / 4 + 2).min(20).min(bits)
+}
+
+/// Mask with the lowest `bits` bits set.
+#[inline]
+pub fn low_mask(bits: u32) -> u64 {
+    if bits >= 64 {
+        !0
+    } else {
+        !(!0 << bits)
+    }
+}
+
+/// The `u64` point of adapters comparing the lowest `bits` bits of a hash,
+/// distinguishing when all bits in `trail_mask` are zero.
+///
+/// Adapters forward their `HashAdapter` point methods to it with
+/// `truncated_point_methods!`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TruncatedPoint {
+    bits: u32,
+    truncate_mask: u64,
+    trail_mask: u64,
+}
+
+impl TruncatedPoint {
+    /// Panics unless `bits` is in `1..=64` and `trail_mask` fits into it.
+    pub fn new(bits: u32, trail_mask: u64) -> Self {
+        assert!(
+            (1..=64).contains(&bits),
+            "point width must be in 1..=64 bits"
+        );
+        let truncate_mask = low_mask(bits);
+        assert!(
+            trail_mask & truncate_mask == trail_mask,
+            "distinguishing mask does not fit into the point"
+        );
+        Self {
+            bits,
+            truncate_mask,
+            trail_mask,
+        }
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// `prefix` followed by `x` in hex, zero-padded to the point width, like
+    /// `foo_00c0ffee` for 32 bits.
+    pub fn hex_name(&self, prefix: &str, x: u64) -> String {
+        format!(
+            "{}{:0width$x}",
+            prefix,
+            x,
+            width = self.bits.div_ceil(4) as usize
+        )
+    }
+
+    /// The point of the full hash `hash`.
+    #[inline]
+    pub fn truncate(&self, hash: u64) -> u64 {
+        hash & self.truncate_mask
+    }
+
+    /// Twenty times the expected trail length.
+    pub fn trail_limit(&self) -> u64 {
+        1_u64
+            .checked_shl(self.trail_mask.count_ones())
+            .map_or(u64::MAX, |len| len.saturating_mul(20))
+    }
+
+    pub fn make_point<R: Rng>(&self, rng: &mut R) -> u64 {
+        rng.next_u64() & self.truncate_mask
+    }
+
+    #[inline]
+    pub fn is_distinguishing(&self, x: u64) -> bool {
+        x & self.trail_mask == 0
+    }
+
+    #[inline]
+    pub fn bifurcation(&self, x: u64) -> bool {
+        x & 1 != 0
+    }
+}
+
+/// The `trail_limit`, `make_point`, `is_distinguishing` and `bifurcation`
+/// methods of a `HashAdapter` over `u64` points, forwarded to the
+/// `TruncatedPoint` in its `point` field.
+macro_rules! truncated_point_methods {
+    () => {
+        fn trail_limit(&self) -> u64 {
+            self.point.trail_limit()
+        }
+
+        fn make_point<R: rand::Rng>(&self, rng: &mut R) -> Self::Point {
+            self.point.make_point(rng)
+        }
+
+        fn is_distinguishing(&self, x: Self::Point) -> bool {
+            self.point.is_distinguishing(x)
+        }
+
+        fn bifurcation(&self, x: Self::Point) -> bool {
+            self.point.bifurcation(x)
+        }
+    };
+}
+pub(crate) use truncated_point_methods;
@@ -0,0 +1,193 @@
+use crate::PointCodec;
+use core::fmt;
+use rand::Rng;
+
+/// A point of exactly `BITS` bits (`1..=128`), stored in the low bits of a
+/// `u128`.  Bits above `BITS` are always zero.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BitPoint<const BITS: usize>(u128);
+
+impl<const BITS: usize> BitPoint<BITS> {
+    const VALID_WIDTH: () = assert!(
+        BITS >= 1 && BITS <= 128,
+        "BitPoint width must be in 1..=128"
+    );
+
+    /// Mask with the lowest `BITS` bits set.
+    pub const MASK: u128 = !0 >> (128 - BITS);
+
+    /// Number of bytes needed to hold the point.
+    pub const BYTES: usize = BITS.div_ceil(8);
+
+    /// Truncate `value` to `BITS` bits.
+    #[inline]
+    pub fn new(value: u128) -> Self {
+        let () = Self::VALID_WIDTH;
+        Self(value & Self::MASK)
+    }
+
+    #[inline]
+    pub fn value(self) -> u128 {
+        self.0
+    }
+
+    #[inline]
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(rng.gen())
+    }
+
+    /// Read up to 16 little-endian bytes, truncating the result to `BITS`.
+    #[inline]
+    pub fn from_le_bytes(bytes: &[u8]) -> Self {
+        let mut buf = [0_u8; 16];
+        let len = bytes.len().min(16);
+        buf[..len].copy_from_slice(&bytes[..len]);
+        Self::new(u128::from_le_bytes(buf))
+    }
+
+    /// Little-endian bytes of the point, only the first `Self::BYTES` can be
+    /// non-zero.
+    #[inline]
+    pub fn to_le_bytes(self) -> [u8; 16] {
+        self.0.to_le_bytes()
+    }
+
+    /// Read up to four little-endian `u32` words, truncating the result to `BITS`.
+    #[inline]
+    pub fn from_le_words(words: &[u32]) -> Self {
+        let value = words
+            .iter()
+            .take(4)
+            .enumerate()
+            .fold(0, |acc, (ii, &w)| acc | (w as u128) << (ii * 32));
+        Self::new(value)
+    }
+
+    /// The point as four little-endian `u32` words.
+    #[inline]
+    pub fn to_le_words(self) -> [u32; 4] {
+        [
+            self.0 as u32,
+            (self.0 >> 32) as u32,
+            (self.0 >> 64) as u32,
+            (self.0 >> 96) as u32,
+        ]
+    }
+
+    #[inline]
+    pub fn bit(self, index: usize) -> bool {
+        assert!(index < BITS, "bit index out of range");
+        (self.0 >> index) & 1 != 0
+    }
+
+    /// Number of leading zero bits, counted from bit `BITS - 1`.
+    #[inline]
+    pub fn leading_zeros(self) -> u32 {
+        self.0.leading_zeros() - (128 - BITS as u32)
+    }
+
+    /// A point is distinguishing when its top `zero_bits` bits are zero.
+    #[inline]
+    pub fn is_distinguishing(self, zero_bits: u32) -> bool {
+        self.leading_zeros() >= zero_bits
+    }
+
+    /// Bifurcation on the lowest bit, which is independent of the bits used
+    /// by `is_distinguishing`.
+    #[inline]
+    pub fn bifurcation(self) -> bool {
+        self.0 & 1 != 0
+    }
+
+    /// Trail limit for points distinguished by `zero_bits` leading zeros.
+    #[inline]
+    pub fn trail_limit(zero_bits: u32) -> u64 {
+        1_u64
+            .checked_shl(zero_bits)
+            .map_or(u64::MAX, |len| len.saturating_mul(20))
+    }
+}
+
+impl<const BITS: usize> fmt::Debug for BitPoint<BITS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:0width$x}", self.0, width = BITS.div_ceil(4))
+    }
+}
+
+impl<const BITS: usize> fmt::LowerHex for BitPoint<BITS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl<const BITS: usize> PointCodec for BitPoint<BITS> {
+    const SIZE: usize = Self::BYTES;
+
+    #[inline]
+    fn encode(&self, out: &mut [u8]) {
+        out.copy_from_slice(&self.to_le_bytes()[..Self::BYTES]);
+    }
+
+    #[inline]
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES {
+            return None;
+        }
+        let point = Self::from_le_bytes(bytes);
+        // Reject encodings with bits set above the point width.
+        (point.to_le_bytes()[..Self::BYTES] == *bytes).then_some(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitPoint;
+    use crate::PointCodec;
+
+    #[test]
+    fn truncation() {
+        assert_eq!(BitPoint::<40>::MASK, 0xff_ffff_ffff);
+        assert_eq!(BitPoint::<40>::new(!0).value(), 0xff_ffff_ffff);
+        assert_eq!(BitPoint::<128>::new(!0).value(), !0);
+        assert_eq!(BitPoint::<72>::BYTES, 9);
+        assert_eq!(
+            BitPoint::<96>::from_le_words(&[1, 2, 3, 4]).to_le_words(),
+            [1, 2, 3, 0]
+        );
+        assert_eq!(
+            BitPoint::<12>::from_le_bytes(&[0xff, 0xff, 0xff]).value(),
+            0xfff
+        );
+    }
+
+    #[test]
+    fn distinguishing() {
+        assert_eq!(BitPoint::<40>::new(0).leading_zeros(), 40);
+        assert_eq!(BitPoint::<40>::new(1 << 39).leading_zeros(), 0);
+        assert_eq!(BitPoint::<72>::new(1 << 60).leading_zeros(), 11);
+        assert_eq!(BitPoint::<128>::new(1).leading_zeros(), 127);
+
+        let x = BitPoint::<96>::new(0x0000_0fff_ffff_ffff_ffff_ffff);
+        assert!(x.is_distinguishing(20));
+        assert!(!x.is_distinguishing(21));
+        assert!(x.bifurcation());
+        assert!(x.bit(75));
+        assert!(!x.bit(76));
+    }
+
+    #[test]
+    fn codec() {
+        let x = BitPoint::<72>::new(0x12_3456_789a_bcde_f012);
+        let bytes = x.to_bytes();
+        assert_eq!(
+            bytes,
+            [0x12, 0xf0, 0xde, 0xbc, 0x9a, 0x78, 0x56, 0x34, 0x12]
+        );
+        assert_eq!(BitPoint::<72>::decode(&bytes), Some(x));
+        assert_eq!(
+            BitPoint::<12>::decode(&[0xff, 0x0f]).unwrap().value(),
+            0xfff
+        );
+        assert_eq!(BitPoint::<12>::decode(&[0xff, 0xff]), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Merkle–Damgård hashes with 64-byte blocks, described by their
+//! compression function.
+
+/// Bytes per block of every `BlockHash`.
+pub const BLOCK_SIZE: usize = 64;
+
+/// A Merkle–Damgård hash: a state, a compression function over 64-byte
+/// blocks and the standard `0x80`, zeros, 64-bit length padding.
+pub trait BlockHash {
+    /// Lowercase name used when reporting collisions.
+    const NAME: &'static str;
+    /// Whether words and the padded length are big-endian.
+    const BIG_ENDIAN: bool;
+
+    type State: Copy + Send + Sync;
+    const INIT: Self::State;
+
+    fn compress(state: &mut Self::State, block: &[u8; BLOCK_SIZE]);
+
+    /// The digest of a message which ended with `state`.
+    fn output(state: &Self::State) -> Vec<u8>;
+
+    /// The first 16 bytes of `output`, without allocating.
+    fn output_prefix(state: &Self::State) -> [u8; 16];
+
+    /// Padding for a message of `len` bytes, filling up its last block.
+    fn padding(len: u64) -> Vec<u8> {
+        let zeros = (BLOCK_SIZE * 2 - 9 - len as usize % BLOCK_SIZE) % BLOCK_SIZE;
+        let mut pad = vec![0x80];
+        pad.resize(1 + zeros, 0);
+        let bit_len = len.wrapping_mul(8);
+        if Self::BIG_ENDIAN {
+            pad.extend_from_slice(&bit_len.to_be_bytes());
+        } else {
+            pad.extend_from_slice(&bit_len.to_le_bytes());
+        }
+        pad
+    }
+}
+
+/// Run the compression function over `data`, which must be a whole number of
+/// blocks.
+pub fn compress_blocks<H: BlockHash>(state: &mut H::State, data: &[u8]) {
+    assert_eq!(
+        data.len() % BLOCK_SIZE,
+        0,
+        "data is not a whole number of blocks"
+    );
+    for block in data.chunks_exact(BLOCK_SIZE) {
+        H::compress(state, block.try_into().unwrap());
+    }
+}
+
+/// The digest of `data`.
+pub fn digest<H: BlockHash>(data: &[u8]) -> Vec<u8> {
+    let mut message = data.to_vec();
+    message.extend_from_slice(&H::padding(data.len() as u64));
+    let mut state = H::INIT;
+    compress_blocks::<H>(&mut state, &message);
+    H::output(&state)
+}
+
+/// Load a block as sixteen little-endian words.
+#[inline]
+pub fn le_words(block: &[u8; BLOCK_SIZE]) -> [u32; 16] {
+    let mut words = [0_u32; 16];
+    for (word, chunk) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+/// Load a block as sixteen big-endian words.
+#[inline]
+pub fn be_words(block: &[u8; BLOCK_SIZE]) -> [u32; 16] {
+    let mut words = [0_u32; 16];
+    for (word, chunk) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+/// Serialize `words` into `out`, stopping at whichever ends first.
+#[inline]
+pub(crate) fn write_words(words: &[u32], out: &mut [u8], big_endian: bool) {
+    for (chunk, word) in out.chunks_mut(4).zip(words) {
+        let bytes = if big_endian {
+            word.to_be_bytes()
+        } else {
+            word.to_le_bytes()
+        };
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockHash;
+    use crate::md5::Md5;
+    use crate::sha256::Sha256;
+
+    #[test]
+    fn padding() {
+        for len in 0..200 {
+            assert_eq!((len + Md5::padding(len).len() as u64) % 64, 0);
+        }
+        assert_eq!(Md5::padding(55).len(), 9);
+        assert_eq!(Md5::padding(56).len(), 72);
+        assert_eq!(Md5::padding(3)[53..], [24, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(Sha256::padding(3)[53..], [0, 0, 0, 0, 0, 0, 0, 24]);
+    }
+}
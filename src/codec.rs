@@ -0,0 +1,91 @@
+/// Fixed-size binary encoding of points.
+///
+/// Every value of a type is encoded into exactly `SIZE` bytes, so records of
+/// points can be stored back to back without any framing.  Integers are
+/// encoded as little-endian, arrays element by element.
+pub trait PointCodec: Sized {
+    /// Number of bytes produced by `encode`.
+    const SIZE: usize;
+
+    /// Write the point into `out`.
+    ///
+    /// Panics if `out.len() != Self::SIZE`.
+    fn encode(&self, out: &mut [u8]);
+
+    /// Read a point from `bytes`, returns `None` if `bytes.len() != Self::SIZE`.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+
+    /// Encode the point into a freshly allocated buffer.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0; Self::SIZE];
+        self.encode(&mut out);
+        out
+    }
+}
+
+macro_rules! impl_codec_for_int {
+    ($($t:ty),*) => {$(
+        impl PointCodec for $t {
+            const SIZE: usize = core::mem::size_of::<$t>();
+
+            #[inline]
+            fn encode(&self, out: &mut [u8]) {
+                out.copy_from_slice(&self.to_le_bytes());
+            }
+
+            #[inline]
+            fn decode(bytes: &[u8]) -> Option<Self> {
+                Some(<$t>::from_le_bytes(bytes.try_into().ok()?))
+            }
+        }
+    )*};
+}
+
+impl_codec_for_int!(u8, u16, u32, u64, u128);
+
+impl<T: PointCodec + Copy + Default, const N: usize> PointCodec for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    #[inline]
+    fn encode(&self, out: &mut [u8]) {
+        assert_eq!(out.len(), Self::SIZE, "output buffer has wrong size");
+        for (item, chunk) in self.iter().zip(out.chunks_exact_mut(T::SIZE)) {
+            item.encode(chunk);
+        }
+    }
+
+    #[inline]
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::SIZE {
+            return None;
+        }
+        let mut result = [T::default(); N];
+        for (item, chunk) in result.iter_mut().zip(bytes.chunks_exact(T::SIZE)) {
+            *item = T::decode(chunk)?;
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PointCodec;
+
+    #[test]
+    fn roundtrip() {
+        let x = 0x0123_4567_89ab_cdef_u64;
+        assert_eq!(x.to_bytes(), [0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01]);
+        assert_eq!(u64::decode(&x.to_bytes()), Some(x));
+
+        let words = [0x266e2670_u32, 0x9b8a1b87, 0x923fd523];
+        assert_eq!(<[u32; 3]>::SIZE, 12);
+        assert_eq!(<[u32; 3]>::decode(&words.to_bytes()), Some(words));
+
+        let bytes = *b"hash-collider";
+        assert_eq!(bytes.to_bytes(), b"hash-collider");
+        assert_eq!(<[u8; 13]>::decode(&bytes), Some(bytes));
+
+        assert_eq!(u128::decode(&[0; 15]), None);
+        assert_eq!(<[u32; 2]>::decode(&[0; 9]), None);
+    }
+}
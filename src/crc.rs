@@ -0,0 +1,453 @@
+//! Direct collisions and preimages for CRCs.
+//!
+//! For messages of a fixed length a CRC is affine over GF(2): flipping a
+//! message bit always flips the same checksum bits.  So instead of walking
+//! trails, collisions and preimages are found by solving a linear system
+//! over the free bits of a message template.
+
+use crate::adapters::low_mask;
+use crate::error::{Error, Result};
+use crate::job::Job;
+use crate::printer::hex;
+use crate::registry::Runnable;
+use crate::Stats;
+use rand::Rng;
+use std::ops::Range;
+
+/// CRC parameters in the usual Rocksoft model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc {
+    pub name: &'static str,
+    /// Checksum width in bits, `1..=64`.
+    pub width: u32,
+    /// Generator polynomial without the top bit, not reflected.
+    pub poly: u64,
+    pub init: u64,
+    /// Process input bytes least significant bit first.
+    pub refin: bool,
+    /// Reflect the register before the final xor.
+    pub refout: bool,
+    pub xorout: u64,
+}
+
+/// CRC-32 used by zlib, gzip, PNG and Ethernet.
+pub const CRC32: Crc = Crc {
+    name: "crc32",
+    width: 32,
+    poly: 0x04c11db7,
+    init: 0xffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffff,
+};
+
+/// CRC-32C (Castagnoli) used by iSCSI, ext4 and SSE4.2.
+pub const CRC32C: Crc = Crc {
+    name: "crc32c",
+    width: 32,
+    poly: 0x1edc6f41,
+    ..CRC32
+};
+
+/// Non-reflected CRC-32 used by bzip2.
+pub const CRC32_BZIP2: Crc = Crc {
+    name: "crc32-bzip2",
+    refin: false,
+    refout: false,
+    ..CRC32
+};
+
+/// CRC-64 used by xz.
+pub const CRC64_XZ: Crc = Crc {
+    name: "crc64-xz",
+    width: 64,
+    poly: 0x42f0e1eba9ea3693,
+    init: !0,
+    refin: true,
+    refout: true,
+    xorout: !0,
+};
+
+/// All presets, for lookup by name.
+pub const PRESETS: [Crc; 4] = [CRC32, CRC32C, CRC32_BZIP2, CRC64_XZ];
+
+fn reflect(x: u64, width: u32) -> u64 {
+    x.reverse_bits() >> (64 - width)
+}
+
+impl Crc {
+    pub fn by_name(name: &str) -> Option<Self> {
+        PRESETS.iter().copied().find(|crc| crc.name == name)
+    }
+
+    /// Bitwise checksum of `data`.
+    pub fn checksum(&self, data: &[u8]) -> u64 {
+        let mask = low_mask(self.width);
+        let mut crc = self.init & mask;
+        if self.refin {
+            let poly = reflect(self.poly, self.width);
+            crc = reflect(crc, self.width);
+            for &byte in data {
+                for ii in 0..8 {
+                    let feedback = (crc ^ (byte >> ii) as u64) & 1 != 0;
+                    crc >>= 1;
+                    if feedback {
+                        crc ^= poly;
+                    }
+                }
+            }
+            if !self.refout {
+                crc = reflect(crc, self.width);
+            }
+        } else {
+            for &byte in data {
+                for ii in (0..8).rev() {
+                    let feedback = ((crc >> (self.width - 1)) ^ (byte >> ii) as u64) & 1 != 0;
+                    crc = (crc << 1) & mask;
+                    if feedback {
+                        crc ^= self.poly;
+                    }
+                }
+            }
+            if self.refout {
+                crc = reflect(crc, self.width);
+            }
+        }
+        (crc ^ self.xorout) & mask
+    }
+}
+
+/// A message with some bits left for the solver to choose.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    data: Vec<u8>,
+    /// Free bit positions, `8 * byte + bit` with bit 0 the least significant.
+    free: Vec<usize>,
+}
+
+impl Template {
+    /// A template without free bits.
+    pub fn new<D: Into<Vec<u8>>>(data: D) -> Self {
+        Self {
+            data: data.into(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Append `count` zero bytes and make them free.
+    pub fn with_free_suffix(mut self, count: usize) -> Self {
+        let start = self.data.len();
+        self.data.resize(start + count, 0);
+        self.with_free_bytes(start..start + count)
+    }
+
+    /// Make all bits of the bytes in `range` free.
+    pub fn with_free_bytes(self, range: Range<usize>) -> Self {
+        self.with_free_bits(range.start * 8..range.end * 8)
+    }
+
+    /// Make the bits at `positions` free.
+    pub fn with_free_bits<I: IntoIterator<Item = usize>>(mut self, positions: I) -> Self {
+        for pos in positions {
+            assert!(
+                pos < self.data.len() * 8,
+                "free bit outside of the template"
+            );
+            if !self.free.contains(&pos) {
+                self.free.push(pos);
+            }
+        }
+        self
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn free_bits(&self) -> &[usize] {
+        &self.free
+    }
+
+    /// The template with the free bits selected by `flips` flipped.
+    fn flip(&self, flips: impl Iterator<Item = usize>) -> Vec<u8> {
+        let mut data = self.data.clone();
+        for ii in flips {
+            let pos = self.free[ii];
+            data[pos / 8] ^= 1 << (pos % 8);
+        }
+        data
+    }
+}
+
+/// Set of variables, one bit per free bit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BitSet(Vec<u64>);
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
+    }
+
+    fn unit(len: usize, index: usize) -> Self {
+        let mut set = Self::new(len);
+        set.0[index / 64] |= 1 << (index % 64);
+        set
+    }
+
+    fn xor(&mut self, other: &Self) {
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a ^= b;
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.0.len() * 64).filter(|ii| self.0[ii / 64] >> (ii % 64) & 1 != 0)
+    }
+}
+
+/// Row-reduced span of the checksum differences of the free bits.
+struct System {
+    /// Basis vectors by pivot bit, with the variables that produce them.
+    basis: Vec<Option<(u64, BitSet)>>,
+    /// Variable sets that do not change the checksum.
+    kernel: Vec<BitSet>,
+    vars: usize,
+}
+
+impl System {
+    fn new(width: u32, columns: &[u64]) -> Self {
+        let vars = columns.len();
+        let mut system = Self {
+            basis: vec![None; width as usize],
+            kernel: Vec::new(),
+            vars,
+        };
+        for (ii, &column) in columns.iter().enumerate() {
+            let (rest, combo) = system.reduce(column, BitSet::unit(vars, ii));
+            if rest == 0 {
+                system.kernel.push(combo);
+            } else {
+                system.basis[63 - rest.leading_zeros() as usize] = Some((rest, combo));
+            }
+        }
+        system
+    }
+
+    fn reduce(&self, mut value: u64, mut combo: BitSet) -> (u64, BitSet) {
+        while value != 0 {
+            let pivot = 63 - value.leading_zeros() as usize;
+            match &self.basis[pivot] {
+                Some((vector, vars)) => {
+                    value ^= vector;
+                    combo.xor(vars);
+                }
+                None => break,
+            }
+        }
+        (value, combo)
+    }
+
+    /// Variables whose checksum differences add up to `target`.
+    fn solve(&self, target: u64) -> Option<BitSet> {
+        let (rest, combo) = self.reduce(target, BitSet::new(self.vars));
+        (rest == 0).then_some(combo)
+    }
+}
+
+/// Solves for collisions and preimages of the low `bits` bits of a CRC.
+pub struct CrcSolver {
+    crc: Crc,
+    mask: u64,
+}
+
+impl CrcSolver {
+    pub fn new(crc: Crc, bits: u32) -> Self {
+        assert!((1..=crc.width).contains(&bits), "bits must be in 1..=width");
+        Self {
+            crc,
+            mask: low_mask(bits),
+        }
+    }
+
+    /// Truncated checksum of `data`.
+    pub fn checksum(&self, data: &[u8]) -> u64 {
+        self.crc.checksum(data) & self.mask
+    }
+
+    /// How the checksum of `template` changes with each free bit.
+    fn columns(&self, template: &Template) -> Vec<u64> {
+        let base = self.checksum(&template.data);
+        (0..template.free.len())
+            .map(|ii| self.checksum(&template.flip(core::iter::once(ii))) ^ base)
+            .collect()
+    }
+
+    /// Fill in `template` so its truncated checksum is `target`.
+    pub fn preimage(&self, template: &Template, target: u64) -> Option<Vec<u8>> {
+        let system = System::new(self.crc.width, &self.columns(template));
+        let vars = system.solve((target & self.mask) ^ self.checksum(&template.data))?;
+        Some(template.flip(vars.iter()))
+    }
+
+    /// Up to `count` distinct pairs of different messages filled in from `a`
+    /// and `b` with equal truncated checksums.  Further pairs are found by
+    /// adding random kernel vectors to the first solution.
+    pub fn collisions<R: Rng>(
+        &self,
+        a: &Template,
+        b: &Template,
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let split = a.free.len();
+        let mut columns = self.columns(a);
+        columns.extend(self.columns(b));
+        let system = System::new(self.crc.width, &columns);
+        let Some(solution) = system.solve(self.checksum(&a.data) ^ self.checksum(&b.data)) else {
+            return Vec::new();
+        };
+
+        let messages = |vars: &BitSet| {
+            (
+                a.flip(vars.iter().filter(|&ii| ii < split)),
+                b.flip(vars.iter().filter(|&ii| ii >= split).map(|ii| ii - split)),
+            )
+        };
+        let mut found = Vec::new();
+        let mut vars = solution;
+        // Bounded, as small kernels may not hold `count` different pairs.
+        for _ in 0..count.saturating_mul(4).max(16) {
+            let (msg_a, msg_b) = messages(&vars);
+            if msg_a != msg_b && !found.contains(&(msg_a.clone(), msg_b.clone())) {
+                found.push((msg_a, msg_b));
+                if found.len() == count {
+                    break;
+                }
+            }
+            if system.kernel.is_empty() {
+                break;
+            }
+            for kernel in &system.kernel {
+                if rng.gen() {
+                    vars.xor(kernel);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// A `Runnable` which solves for collisions of two templates, or for a
+/// preimage of the first one when a target is given.
+pub struct CrcJob {
+    solver: CrcSolver,
+    a: Template,
+    b: Template,
+    target: Option<u64>,
+}
+
+impl CrcJob {
+    pub fn new(solver: CrcSolver, a: Template, b: Template, target: Option<u64>) -> Self {
+        Self {
+            solver,
+            a,
+            b,
+            target,
+        }
+    }
+}
+
+impl Runnable for CrcJob {
+    fn run(&mut self, job: &Job) -> Result<Stats> {
+        let reporter = job.reporter()?;
+        let name = self.solver.crc.name;
+        let mut stats = Stats::default();
+        if let Some(target) = self.target {
+            let data = self.solver.preimage(&self.a, target).ok_or_else(|| {
+                Error::Invalid(format!("no preimage of {:x} in the template", target))
+            })?;
+            // Preimages have no second message, `b` is left empty.
+            reporter.report_bytes(
+                &data,
+                &[],
+                format_args!(
+                    "found preimage! (a, {}) {}={:x}",
+                    hex(&data),
+                    name,
+                    self.solver.checksum(&data)
+                ),
+            );
+            return Ok(stats);
+        }
+
+        let count = job.stop.collisions.unwrap_or(1) as usize;
+        let found = self
+            .solver
+            .collisions(&self.a, &self.b, count, &mut rand::thread_rng());
+        if found.is_empty() {
+            return Err(Error::Invalid(
+                "templates have too few free bits for a collision".to_owned(),
+            ));
+        }
+        for (a, b) in &found {
+            let checksum = self.solver.checksum(a);
+            assert_eq!(checksum, self.solver.checksum(b));
+            reporter.report_bytes(
+                a,
+                b,
+                format_args!(
+                    "found collision! (a, {}) (b, {}) {}={:x}",
+                    hex(a),
+                    hex(b),
+                    name,
+                    checksum
+                ),
+            );
+        }
+        stats.collisions = found.len() as u64;
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrcSolver, Template, PRESETS};
+
+    #[test]
+    fn check_values() {
+        let expected = [0xcbf43926, 0xe3069283, 0xfc891918, 0x995dc9bbdf1939fa];
+        for (crc, check) in PRESETS.iter().zip(expected) {
+            assert_eq!(crc.checksum(b"123456789"), check, "{}", crc.name);
+        }
+    }
+
+    #[test]
+    fn solve() {
+        let mut rng = rand::thread_rng();
+        for crc in PRESETS {
+            for bits in [crc.width, 20] {
+                let solver = CrcSolver::new(crc, bits);
+                let a = Template::new(&b"hello "[..]).with_free_suffix(9);
+                let b = Template::new(&b"world"[..]).with_free_suffix(9);
+
+                let data = solver.preimage(&a, 0x1234_5678_9abc).unwrap();
+                assert_eq!(&data[..6], b"hello ");
+                assert_eq!(solver.checksum(&data), 0x1234_5678_9abc & solver.mask);
+
+                for other in [&b, &a] {
+                    let found = solver.collisions(&a, other, 3, &mut rng);
+                    assert_eq!(found.len(), 3);
+                    for (x, y) in found {
+                        assert_ne!(x, y);
+                        assert_eq!(solver.checksum(&x), solver.checksum(&y));
+                    }
+                }
+            }
+        }
+
+        // Two free bits can not reach every 32-bit checksum.
+        let solver = CrcSolver::new(PRESETS[0], 32);
+        let a = Template::new(vec![0; 4]).with_free_bits([0, 9]);
+        assert_eq!(solver.preimage(&a, 0x1234_5678), None);
+    }
+}
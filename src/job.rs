@@ -0,0 +1,415 @@
+//! Declarative descriptions of collision runs.
+//!
+//! A job names an adapter, its parameters and how to run it.  Jobs are
+//! usually loaded from TOML files, so runs can be versioned and repeated:
+//!
+//! ```toml
+//! adapter = "ahash"
+//! bits = 48
+//! threads = 8
+//! trail_mask = "0xffff"
+//! checkpoint = "ahash48.trails"
+//!
+//! [params]
+//! prefix_a = "hello"
+//! prefix_b = "world"
+//!
+//! [stop]
+//! collisions = 1
+//! seconds = 3600
+//!
+//! [[output]]
+//! format = "text"
+//!
+//! [[output]]
+//! path = "collisions.jsonl"
+//! format = "json"
+//! ```
+//!
+//! Relative paths, including parameters whose names end in `_file` or
+//! `_dir`, are resolved against the directory of the job file.
+
+use crate::adapters::{default_trail_bits, low_mask};
+use crate::error::{Error, Result};
+use crate::registry::Registry;
+use crate::{OutputFormat, Reporter, Stats, StopConditions};
+use core::fmt::Display;
+use core::str::FromStr;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// String parameters of an adapter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, name: K, value: V) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn get_or<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.get(name).unwrap_or(default)
+    }
+
+    /// Parse the parameter `name`, or return `default` when it is missing.
+    pub fn parse_or<T>(&self, name: &str, default: T) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.get(name) {
+            None => Ok(default),
+            Some(value) => value.parse().map_err(|e| {
+                Error::Invalid(format!("invalid value for parameter {}: {}", name, e))
+            }),
+        }
+    }
+
+    /// Fail if any parameter is not in `known`.
+    pub fn check_known(&self, known: &[&str]) -> Result<()> {
+        match self.0.keys().find(|name| !known.contains(&name.as_str())) {
+            None => Ok(()),
+            Some(name) => Err(Error::Invalid(format!("unknown parameter {:?}", name))),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+/// A place found collisions are written to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Output {
+    Stdout(OutputFormat),
+    File(PathBuf, OutputFormat),
+}
+
+/// A validated collision run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    pub adapter: String,
+    pub params: Params,
+    /// Width of the colliding hash prefix in bits.
+    pub bits: u32,
+    /// Distinguishing point mask, derived from `bits` when `None`.
+    pub trail_mask: Option<u64>,
+    pub threads: usize,
+    /// Seconds between stat lines.
+    pub interval: u64,
+    pub stop: StopConditions,
+    /// Trail table checkpoint, loaded before and saved during the run.  It
+    /// is rejected when saved by a job with another `fingerprint`.
+    pub checkpoint: Option<PathBuf>,
+    pub checkpoint_interval: Duration,
+    /// Collision sinks, stdout in the text format when empty.
+    pub outputs: Vec<Output>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobFile {
+    adapter: String,
+    bits: u32,
+    #[serde(default)]
+    params: BTreeMap<String, toml::Value>,
+    threads: Option<usize>,
+    trail_mask: Option<String>,
+    interval: Option<u64>,
+    #[serde(default)]
+    stop: StopFile,
+    checkpoint: Option<PathBuf>,
+    checkpoint_interval: Option<u64>,
+    #[serde(default)]
+    output: Vec<OutputFile>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StopFile {
+    collisions: Option<u64>,
+    seconds: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OutputFile {
+    path: Option<PathBuf>,
+    format: Option<String>,
+}
+
+fn param_to_string(name: &str, value: toml::Value) -> Result<String> {
+    use toml::Value;
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Integer(i) => Ok(i.to_string()),
+        Value::Float(f) => Ok(f.to_string()),
+        Value::Boolean(b) => Ok(b.to_string()),
+        // Lists, such as crate metadata strings, are passed comma-separated.
+        Value::Array(items) => Ok(items
+            .into_iter()
+            .map(|item| param_to_string(name, item))
+            .collect::<Result<Vec<_>>>()?
+            .join(",")),
+        _ => Err(Error::Invalid(format!(
+            "parameter {} must be a string, number, boolean or list",
+            name
+        ))),
+    }
+}
+
+/// Parse a hexadecimal mask, with or without a `0x` prefix.
+pub fn parse_mask(s: &str) -> Result<u64> {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    u64::from_str_radix(hex, 16)
+        .map_err(|e| Error::Invalid(format!("invalid trail mask {:?}: {}", s, e)))
+}
+
+impl Job {
+    /// A job with default settings: all cores, stat lines every second, no
+    /// stop conditions and collisions printed to stdout.
+    pub fn new(adapter: &str, bits: u32) -> Self {
+        Self {
+            adapter: adapter.to_owned(),
+            params: Params::new(),
+            bits,
+            trail_mask: None,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            interval: 1,
+            stop: StopConditions::default(),
+            checkpoint: None,
+            checkpoint_interval: Duration::from_secs(600),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Parse and validate a TOML job description.  Relative paths are kept
+    /// as they are.
+    pub fn from_toml(s: &str) -> Result<Self> {
+        let file: JobFile = toml::from_str(s)?;
+        let mut job = Self::new(&file.adapter, file.bits);
+        for (name, value) in file.params {
+            let value = param_to_string(&name, value)?;
+            job.params.insert(name, value);
+        }
+        if let Some(threads) = file.threads {
+            job.threads = threads;
+        }
+        job.trail_mask = file.trail_mask.as_deref().map(parse_mask).transpose()?;
+        if let Some(interval) = file.interval {
+            job.interval = interval;
+        }
+        job.stop = StopConditions {
+            collisions: file.stop.collisions,
+            duration: file.stop.seconds.map(Duration::from_secs),
+        };
+        job.checkpoint = file.checkpoint;
+        if let Some(secs) = file.checkpoint_interval {
+            job.checkpoint_interval = Duration::from_secs(secs);
+        }
+        for output in file.output {
+            let format = match output.format {
+                Some(format) => format.parse().map_err(Error::Invalid)?,
+                None => OutputFormat::Text,
+            };
+            job.outputs.push(match output.path {
+                Some(path) => Output::File(path, format),
+                None => Output::Stdout(format),
+            });
+        }
+        job.validate()?;
+        Ok(job)
+    }
+
+    /// Load a job file, resolving relative paths against its directory.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut job = Self::from_toml(&std::fs::read_to_string(path)?)?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        if let Some(checkpoint) = &mut job.checkpoint {
+            *checkpoint = base.join(&*checkpoint);
+        }
+        for output in &mut job.outputs {
+            if let Output::File(path, _) = output {
+                *path = base.join(&*path);
+            }
+        }
+        let files = job
+            .params
+            .iter()
+            .filter(|(name, _)| name.ends_with("_file") || name.ends_with("_dir"))
+            .map(|(name, value)| (name.to_owned(), base.join(value)))
+            .collect::<Vec<_>>();
+        for (name, path) in files {
+            job.params.insert(name, path.to_string_lossy());
+        }
+        Ok(job)
+    }
+
+    /// Check the settings which do not depend on the adapter, the adapter
+    /// and its parameters are checked by `Registry::build`.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(Error::Invalid(msg));
+        if !(1..=128).contains(&self.bits) {
+            return invalid("bits must be in 1..=128".to_owned());
+        }
+        if let Some(mask) = self.trail_mask {
+            if mask & low_mask(self.bits) != mask {
+                return invalid(format!("trail mask {:#x} is wider than the point", mask));
+            }
+        }
+        if self.threads == 0 {
+            return invalid("threads must be at least 1".to_owned());
+        }
+        if self.interval == 0 {
+            return invalid("interval must be at least 1 second".to_owned());
+        }
+        Ok(())
+    }
+
+    /// Open all outputs of the job.
+    pub fn reporter(&self) -> Result<Reporter> {
+        if self.outputs.is_empty() {
+            return Ok(Reporter::default());
+        }
+        let mut reporter = Reporter::empty();
+        for output in &self.outputs {
+            reporter = match output {
+                Output::Stdout(format) => reporter.with_stdout(*format),
+                Output::File(path, format) => reporter.with_file(path, *format)?,
+            };
+        }
+        Ok(reporter)
+    }
+
+    /// Distinguishing point mask, `trail_mask` or the default for `bits`.
+    pub fn trail_mask_or_default(&self) -> u64 {
+        self.trail_mask
+            .unwrap_or_else(|| low_mask(default_trail_bits(self.bits)))
+    }
+
+    /// The settings deciding which trails a run produces: the adapter, its
+    /// parameters, `bits` and the distinguishing mask.  Checkpoints are only
+    /// loaded by jobs with the same fingerprint.
+    pub fn fingerprint(&self) -> String {
+        let mut fingerprint = format!(
+            "adapter={} bits={} trail_mask={:#x}",
+            self.adapter,
+            self.bits,
+            self.trail_mask_or_default()
+        );
+        for (name, value) in self.params.iter() {
+            fingerprint += &format!(" {}={:?}", name, value);
+        }
+        fingerprint
+    }
+
+    /// Run the job with one of the built-in adapters.
+    pub fn run(&self) -> Result<Stats> {
+        self.run_with(&Registry::builtin())
+    }
+
+    /// Run the job with an adapter from `registry`, until one of the stop
+    /// conditions is met.
+    pub fn run_with(&self, registry: &Registry) -> Result<Stats> {
+        self.validate()?;
+        registry.build(self)?.run(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Job, Output};
+    use crate::OutputFormat;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn parse_job_file() {
+        let job = Job::from_toml(
+            r#"
+            adapter = "ahash"
+            bits = 48
+            threads = 3
+            trail_mask = "0xffff"
+            checkpoint = "ahash48.trails"
+
+            [params]
+            prefix_a = "hello"
+            key0 = 42
+
+            [stop]
+            collisions = 2
+            seconds = 60
+
+            [[output]]
+
+            [[output]]
+            path = "collisions.jsonl"
+            format = "json"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(job.adapter, "ahash");
+        assert_eq!(job.bits, 48);
+        assert_eq!(job.threads, 3);
+        assert_eq!(job.trail_mask, Some(0xffff));
+        assert_eq!(job.params.get("prefix_a"), Some("hello"));
+        assert_eq!(job.params.parse_or("key0", 0_u128).unwrap(), 42);
+        assert_eq!(job.stop.collisions, Some(2));
+        assert_eq!(job.stop.duration, Some(Duration::from_secs(60)));
+        assert_eq!(job.checkpoint, Some(PathBuf::from("ahash48.trails")));
+        assert_eq!(
+            job.outputs,
+            [
+                Output::Stdout(OutputFormat::Text),
+                Output::File("collisions.jsonl".into(), OutputFormat::Json)
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint() {
+        let mut job = Job::new("ahash", 48);
+        job.params.insert("prefix_a", "hello");
+        assert_eq!(
+            job.fingerprint(),
+            "adapter=ahash bits=48 trail_mask=0x3fff prefix_a=\"hello\""
+        );
+
+        let mut other = job.clone();
+        other.threads += 1;
+        other.checkpoint = Some("ahash48.trails".into());
+        assert_eq!(other.fingerprint(), job.fingerprint());
+        other.trail_mask = Some(0xffff);
+        assert_ne!(other.fingerprint(), job.fingerprint());
+        other = job.clone();
+        other.params.insert("key0", "1");
+        assert_ne!(other.fingerprint(), job.fingerprint());
+    }
+
+    #[test]
+    fn reject_invalid_jobs() {
+        let cases = [
+            "adapter = 'std'\nbits = 129",
+            "adapter = 'std'\nbits = 32\nthreads = 0",
+            "adapter = 'std'\nbits = 32\ntrail_mask = '0x1ffffffff'",
+            "adapter = 'std'\nbits = 32\nunknown = 1",
+            "adapter = 'std'\nbits = 32\n[[output]]\nformat = 'xml'",
+        ];
+        for case in cases {
+            assert!(Job::from_toml(case).is_err(), "accepted {:?}", case);
+        }
+    }
+}
@@ -1,5 +1,21 @@
+pub mod adapters;
+mod bitpoint;
+pub mod block_hash;
+mod codec;
+pub mod crc;
+mod error;
+pub mod job;
+pub mod md5;
+pub mod polyhash;
 mod printer;
+pub mod registry;
+#[cfg(feature = "rustc")]
+pub mod rustc;
+pub mod sha1;
+pub mod sha256;
+pub mod siphash;
 mod stats;
+mod trace;
 
 use core::fmt::Debug;
 use core::hash::Hash;
@@ -7,10 +23,18 @@ use core::ops::ControlFlow;
 use core::sync::atomic::{AtomicBool, Ordering};
 use rand::Rng;
 use std::collections::{hash_map::Entry, HashMap};
+use std::io::{self, Read, Write};
 use std::sync::Mutex;
 
-pub use crate::printer::stat_printer;
-use crate::stats::{AtomicStats, Stats};
+pub use crate::bitpoint::BitPoint;
+pub use crate::codec::PointCodec;
+pub use crate::error::Error;
+pub use crate::printer::{
+    stat_printer, stat_printer_until, OutputFormat, Reporter, StopConditions,
+};
+use crate::stats::AtomicStats;
+pub use crate::stats::Stats;
+pub use crate::trace::{trace_collision, TraceResult, TrailInfo};
 
 pub trait StopSignal {
     fn stop(&self);
@@ -29,19 +53,6 @@ pub trait HashAdapter: Sync {
     }
 }
 
-#[derive(Clone)]
-struct TrailInfo<P> {
-    start: P,
-    length: u64,
-}
-
-enum TraceResult<P> {
-    GoodCollision(P, P),
-    SelfCollision(P, P),
-    RobinHood(P),
-    NotFound,
-}
-
 type TrailRecords<A> =
     HashMap<<A as HashAdapter>::Point, Vec<TrailInfo<<A as HashAdapter>::Point>>>;
 
@@ -67,54 +78,15 @@ impl<A: HashAdapter> Collider<A> {
         self.stats.report()
     }
 
-    fn trace_collision(
-        adapter: &A,
-        a: &TrailInfo<A::Point>,
-        b: &TrailInfo<A::Point>,
-    ) -> TraceResult<A::Point> {
-        let TrailInfo {
-            start: mut a,
-            length: mut a_len,
-        } = a;
-        let TrailInfo {
-            start: mut b,
-            length: mut b_len,
-        } = b;
-
-        while b_len > a_len {
-            let bifurcation = adapter.bifurcation(b);
-            b = adapter.next_point(b, bifurcation);
-            b_len -= 1;
-        }
-
-        while a_len > b_len {
-            let bifurcation = adapter.bifurcation(a);
-            a = adapter.next_point(a, bifurcation);
-            a_len -= 1;
-        }
-
-        if a == b {
-            return TraceResult::RobinHood(a);
-        }
-
-        for _ in 0..a_len {
-            let a_bifurcation = adapter.bifurcation(a);
-            let next_a = adapter.next_point(a, a_bifurcation);
-            let b_bifurcation = adapter.bifurcation(b);
-            let next_b = adapter.next_point(b, b_bifurcation);
-            if next_a == next_b {
-                return match (a_bifurcation, b_bifurcation) {
-                    (false, false) | (true, true) => TraceResult::SelfCollision(a, b),
-                    (true, false) => TraceResult::GoodCollision(a, b),
-                    (false, true) => TraceResult::GoodCollision(b, a),
-                };
-            }
-
-            a = next_a;
-            b = next_b;
-        }
+    /// Number of trails recorded so far.
+    pub fn trail_count(&self) -> usize {
+        self.lock_trails().values().map(Vec::len).sum()
+    }
 
-        TraceResult::NotFound
+    fn lock_trails(&self) -> std::sync::MutexGuard<'_, TrailRecords<A>> {
+        self.trails
+            .lock()
+            .expect("some other thread has crashed and poisoned a mutex")
     }
 
     pub fn run<F: FnOnce(&Self)>(&mut self, count: usize, f: F) {
@@ -137,83 +109,199 @@ impl<A: HashAdapter> Collider<A> {
 
     fn worker(&self) {
         let mut rng = rand::thread_rng();
-        let trail_limit = self.adapter.trail_limit();
 
-        'outer: while self.running.load(Ordering::Relaxed) {
+        while self.running.load(Ordering::Relaxed) {
             let start = self.adapter.make_point(&mut rng);
-            let mut point = start;
-            let mut length = 0;
-
-            // Run a trail
-            while !self.adapter.is_distinguishing(point) {
-                let bifurcation = self.adapter.bifurcation(point);
-                point = self.adapter.next_point(point, bifurcation);
-                length += 1;
-                // The trail is too long, and possibly entered a loop, give up
-                if length > trail_limit {
-                    self.stats.bailouts.fetch_add(1, Ordering::Relaxed);
-                    continue 'outer;
-                }
+            if let Some((end, length)) = self.walk_trail(start) {
+                self.add_trail(start, end, length);
             }
+        }
+    }
 
-            self.stats.trails.fetch_add(1, Ordering::Relaxed);
-            self.stats.hashes.fetch_add(length, Ordering::Relaxed);
-
-            let trail_info = TrailInfo { start, length };
-            let mut check_collisions = None;
-            let mut trails_lock = if let Ok(lock) = self.trails.try_lock() {
-                lock
-            } else {
-                self.stats.lock_contentions.fetch_add(1, Ordering::Relaxed);
-                self.trails
-                    .lock()
-                    .expect("some other thread has crashed and poisoned a mutex")
-            };
+    /// Walk from `start` to the next distinguishing point, returns the end
+    /// point and the trail length, or `None` if the trail limit was hit.
+    pub(crate) fn walk_trail(&self, start: A::Point) -> Option<(A::Point, u64)> {
+        let trail_limit = self.adapter.trail_limit();
+        let mut point = start;
+        let mut length = 0;
+
+        while !self.adapter.is_distinguishing(point) {
+            let bifurcation = self.adapter.bifurcation(point);
+            point = self.adapter.next_point(point, bifurcation);
+            length += 1;
+            // The trail is too long, and possibly entered a loop, give up
+            if length > trail_limit {
+                self.stats.bailouts.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        }
 
-            // You ok clippy?  There are no locks inside the match expression.
-            #[allow(clippy::significant_drop_in_scrutinee)]
-            match trails_lock.entry(point) {
-                Entry::Vacant(v) => {
-                    v.insert(vec![trail_info.clone()]);
-                }
-                Entry::Occupied(mut o) => {
-                    check_collisions = Some(o.get().clone());
-                    o.get_mut().push(trail_info.clone());
-                }
+        self.stats.trails.fetch_add(1, Ordering::Relaxed);
+        self.stats.hashes.fetch_add(length, Ordering::Relaxed);
+        Some((point, length))
+    }
+
+    /// Record the trail `start -> end`, and trace collisions with previous
+    /// trails ending in the same point.
+    pub(crate) fn add_trail(&self, start: A::Point, end: A::Point, length: u64) {
+        let trail_info = TrailInfo { start, length };
+        let mut check_collisions = None;
+        let mut trails_lock = if let Ok(lock) = self.trails.try_lock() {
+            lock
+        } else {
+            self.stats.lock_contentions.fetch_add(1, Ordering::Relaxed);
+            self.lock_trails()
+        };
+
+        // You ok clippy?  There are no locks inside the match expression.
+        #[allow(clippy::significant_drop_in_scrutinee)]
+        match trails_lock.entry(end) {
+            Entry::Vacant(v) => {
+                v.insert(vec![trail_info]);
+            }
+            Entry::Occupied(mut o) => {
+                check_collisions = Some(o.get().clone());
+                o.get_mut().push(trail_info);
             }
+        }
 
-            // Release the lock before the next step, since it's CPU-expensive
-            core::mem::drop(trails_lock);
-
-            // Find collisions with previous trails.
-            if let Some(prev_trails) = check_collisions {
-                for previous in prev_trails {
-                    match Self::trace_collision(&self.adapter, &previous, &trail_info) {
-                        TraceResult::GoodCollision(a, b) => {
-                            self.stats.collisions.fetch_add(1, Ordering::Relaxed);
-                            if self.adapter.report_collision(a, b).is_break() {
-                                self.running.store(false, Ordering::Relaxed);
-                            }
-                        }
-                        TraceResult::SelfCollision(a, b) => {
-                            self.stats.self_collisions.fetch_add(1, Ordering::Relaxed);
-                            if self.adapter.report_self_collision(a, b).is_break() {
-                                self.running.store(false, Ordering::Relaxed);
-                            }
-                        }
-                        TraceResult::RobinHood(_a) => {
-                            self.stats.robin_hoods.fetch_add(1, Ordering::Relaxed);
+        // Release the lock before the next step, since it's CPU-expensive
+        core::mem::drop(trails_lock);
+
+        // Find collisions with previous trails.
+        if let Some(prev_trails) = check_collisions {
+            for previous in prev_trails {
+                match trace_collision(&self.adapter, &previous, &trail_info) {
+                    TraceResult::GoodCollision(a, b) => {
+                        self.stats.collisions.fetch_add(1, Ordering::Relaxed);
+                        if self.adapter.report_collision(a, b).is_break() {
+                            self.running.store(false, Ordering::Relaxed);
                         }
-                        TraceResult::NotFound => {
-                            self.stats.errors.fetch_add(1, Ordering::Relaxed);
+                    }
+                    TraceResult::SelfCollision(a, b) => {
+                        self.stats.self_collisions.fetch_add(1, Ordering::Relaxed);
+                        if self.adapter.report_self_collision(a, b).is_break() {
+                            self.running.store(false, Ordering::Relaxed);
                         }
                     }
+                    TraceResult::RobinHood(_a) => {
+                        self.stats.robin_hoods.fetch_add(1, Ordering::Relaxed);
+                    }
+                    TraceResult::NotFound => {
+                        self.stats.errors.fetch_add(1, Ordering::Relaxed);
+                    }
                 }
             }
         }
     }
 }
 
+/// The steps of a worker, for `benches/collider.rs`.  Not part of the API.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+impl<A: HashAdapter> Collider<A> {
+    pub fn bench_walk_trail(&self, start: A::Point) -> Option<(A::Point, u64)> {
+        self.walk_trail(start)
+    }
+
+    pub fn bench_add_trail(&self, start: A::Point, end: A::Point, length: u64) {
+        self.add_trail(start, end, length)
+    }
+}
+
+const CHECKPOINT_MAGIC: &[u8; 8] = b"HCTRAILS";
+
+impl<A: HashAdapter> Collider<A>
+where
+    A::Point: PointCodec,
+{
+    /// Write all recorded trails to `w`.
+    ///
+    /// The checkpoint is a header (magic, point size and `fingerprint`)
+    /// followed by one `(end, start, length)` record per trail, points
+    /// encoded with `PointCodec` and the length as a little-endian `u64`.
+    /// The fingerprint identifies the adapter configuration, such as
+    /// `Job::fingerprint`, and is checked by `load_trails`.
+    pub fn save_trails<W: Write>(&self, mut w: W, fingerprint: &str) -> io::Result<()> {
+        // Copy the table, so the workers are not blocked by the writes.
+        let trails = self
+            .lock_trails()
+            .iter()
+            .flat_map(|(end, starts)| starts.iter().map(move |info| (*end, *info)))
+            .collect::<Vec<_>>();
+        let size = A::Point::SIZE;
+        w.write_all(CHECKPOINT_MAGIC)?;
+        w.write_all(&(size as u32).to_le_bytes())?;
+        w.write_all(&(fingerprint.len() as u32).to_le_bytes())?;
+        w.write_all(fingerprint.as_bytes())?;
+        let mut record = vec![0; size * 2 + 8];
+        for (end, TrailInfo { start, length }) in trails {
+            end.encode(&mut record[..size]);
+            start.encode(&mut record[size..size * 2]);
+            record[size * 2..].copy_from_slice(&length.to_le_bytes());
+            w.write_all(&record)?;
+        }
+        w.flush()
+    }
+
+    /// Add trails saved by `save_trails` to the table, returns the number
+    /// of trails read.
+    ///
+    /// Fails without adding any trail if the checkpoint was saved with
+    /// another `fingerprint`.  The trails are not checked for collisions
+    /// with each other.
+    pub fn load_trails<R: Read>(&self, mut r: R, fingerprint: &str) -> io::Result<u64> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let size = A::Point::SIZE;
+        let mut header = [0; 16];
+        r.read_exact(&mut header)?;
+        if header[..8] != CHECKPOINT_MAGIC[..] {
+            return Err(invalid("not a trail checkpoint".to_owned()));
+        }
+        if header[8..12] != (size as u32).to_le_bytes() {
+            return Err(invalid(
+                "checkpoint point size does not match the adapter".to_owned(),
+            ));
+        }
+        let len = u32::from_le_bytes(header[12..].try_into().unwrap());
+        let mut saved = Vec::new();
+        r.by_ref().take(len.into()).read_to_end(&mut saved)?;
+        if saved.len() != len as usize {
+            return Err(invalid("truncated checkpoint".to_owned()));
+        }
+        if saved != fingerprint.as_bytes() {
+            return Err(invalid(format!(
+                "checkpoint was saved by another job: {}",
+                String::from_utf8_lossy(&saved)
+            )));
+        }
+
+        let mut data = Vec::new();
+        r.read_to_end(&mut data)?;
+        let records = data.chunks_exact(size * 2 + 8);
+        if !records.remainder().is_empty() {
+            return Err(invalid("truncated checkpoint".to_owned()));
+        }
+        let mut loaded = Vec::with_capacity(records.len());
+        for record in records {
+            let end = A::Point::decode(&record[..size]);
+            let start = A::Point::decode(&record[size..size * 2]);
+            let (Some(end), Some(start)) = (end, start) else {
+                return Err(invalid("invalid point in checkpoint".to_owned()));
+            };
+            let length = u64::from_le_bytes(record[size * 2..].try_into().unwrap());
+            loaded.push((end, TrailInfo { start, length }));
+        }
+
+        let count = loaded.len() as u64;
+        let mut trails = self.lock_trails();
+        for (end, info) in loaded {
+            trails.entry(end).or_default().push(info);
+        }
+        Ok(count)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,4 +354,93 @@ mod tests {
         let thread_count = num_cpus::get();
         collider.run(thread_count, stat_printer(1, 42, ControlFlow::Break(())));
     }
+
+    /// Steps `x -> x + 1`, trails end at multiples of 16.
+    struct Counter;
+
+    impl HashAdapter for Counter {
+        type Point = u8;
+
+        fn trail_limit(&self) -> u64 {
+            16
+        }
+
+        fn make_point<R: Rng>(&self, rng: &mut R) -> Self::Point {
+            rng.gen()
+        }
+
+        fn is_distinguishing(&self, x: Self::Point) -> bool {
+            x % 16 == 0
+        }
+
+        fn bifurcation(&self, _x: Self::Point) -> bool {
+            false
+        }
+
+        fn next_point(&self, x: Self::Point, _bi: bool) -> Self::Point {
+            x.wrapping_add(1)
+        }
+
+        fn report_collision(&self, _a: Self::Point, _b: Self::Point) -> ControlFlow<(), ()> {
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn checkpoint(fingerprint: &str) -> Vec<u8> {
+        let collider = Collider::new(Counter);
+        for start in [1, 17, 40] {
+            let (end, length) = collider.walk_trail(start).unwrap();
+            collider.add_trail(start, end, length);
+        }
+        let mut data = Vec::new();
+        collider.save_trails(&mut data, fingerprint).unwrap();
+        data
+    }
+
+    #[test]
+    fn checkpoint_roundtrip() {
+        let saved = Collider::new(Counter);
+        let loaded = Collider::new(Counter);
+        let data = checkpoint("counter");
+        assert_eq!(saved.load_trails(&data[..], "counter").unwrap(), 3);
+        let mut resaved = Vec::new();
+        saved.save_trails(&mut resaved, "counter").unwrap();
+        assert_eq!(loaded.load_trails(&resaved[..], "counter").unwrap(), 3);
+        assert_eq!(*loaded.lock_trails(), *saved.lock_trails());
+        assert_eq!(
+            loaded.lock_trails()[&48],
+            [TrailInfo {
+                start: 40,
+                length: 8
+            }]
+        );
+    }
+
+    #[test]
+    fn checkpoint_errors() {
+        let collider = Collider::new(Counter);
+        let data = checkpoint("counter");
+        let error = |data: &[u8], fingerprint| {
+            let e = collider.load_trails(data, fingerprint).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            e.to_string()
+        };
+
+        assert!(error(&data, "other").contains("saved by another job: counter"));
+        assert_eq!(
+            error(&data[..data.len() - 1], "counter"),
+            "truncated checkpoint"
+        );
+        assert_eq!(error(&data[..20], "counter"), "truncated checkpoint");
+        let mut magic = data.clone();
+        magic[0] ^= 1;
+        assert_eq!(error(&magic, "counter"), "not a trail checkpoint");
+        let mut size = data.clone();
+        size[8] = 2;
+        assert_eq!(
+            error(&size, "counter"),
+            "checkpoint point size does not match the adapter"
+        );
+        assert_eq!(collider.trail_count(), 0);
+    }
 }
@@ -0,0 +1,153 @@
+//! Command-line front end running the built-in adapters.
+
+use hash_collider::job::{parse_mask, Job, Output};
+use hash_collider::registry::Registry;
+use std::process::exit;
+use std::time::Duration;
+
+const USAGE: &str = "\
+usage: hash-collider <adapter> [options] [--<param> VALUE ...]
+       hash-collider job <file.toml>
+       hash-collider list
+       hash-collider help [adapter]
+
+options:
+    --bits N            width of the colliding hash prefix in bits (default 64)
+    --threads N         worker thread count (default: all cores)
+    --mask HEX          distinguishing point mask (default derived from --bits),
+                        only the lowest bits for adapters taking --trail-bits
+    --format FORMAT     collision output format, text or json (default text)
+    --output PATH       append collisions to PATH instead of stdout
+    --interval SECS     seconds between stat lines (default 1)
+    --checkpoint PATH   load and periodically save the trail table
+    --seconds N         stop after running for N seconds
+    --stop              stop after the first collision
+
+Common adapters are std, ahash, md5-ihv and rustc-typeid, see
+`hash-collider list` for all of them.  Any other --some-param VALUE is
+passed to the adapter as `some_param`, see `hash-collider help <adapter>`
+for the parameters of each adapter.
+";
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Job, String> {
+    let adapter = args.next().ok_or("missing adapter name")?;
+    if adapter == "job" {
+        let path = args.next().ok_or("missing job file")?;
+        if let Some(extra) = args.next() {
+            return Err(format!("unexpected argument {}", extra));
+        }
+        return Job::load(path).map_err(|e| e.to_string());
+    }
+
+    let mut job = Job::new(&adapter, 64);
+    let mut format = None;
+    let mut output = None;
+    while let Some(flag) = args.next() {
+        if flag == "--stop" {
+            job.stop.collisions = Some(1);
+            continue;
+        }
+        let name = flag
+            .strip_prefix("--")
+            .ok_or_else(|| format!("unexpected argument {}", flag))?;
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+        let invalid = |e: &dyn std::fmt::Display| format!("invalid value for {}: {}", flag, e);
+        match name {
+            "bits" => job.bits = value.parse().map_err(|e| invalid(&e))?,
+            "threads" => job.threads = value.parse().map_err(|e| invalid(&e))?,
+            "mask" => job.trail_mask = Some(parse_mask(&value).map_err(|e| invalid(&e))?),
+            "format" => format = Some(value.parse().map_err(|e| invalid(&e))?),
+            "output" => output = Some(value),
+            "interval" => job.interval = value.parse().map_err(|e| invalid(&e))?,
+            "checkpoint" => job.checkpoint = Some(value.into()),
+            "seconds" => {
+                let secs = value.parse().map_err(|e| invalid(&e))?;
+                job.stop.duration = Some(Duration::from_secs(secs));
+            }
+            param => job.params.insert(param.replace('-', "_"), value),
+        }
+    }
+
+    let format = format.unwrap_or_default();
+    job.outputs.push(match output {
+        Some(path) => Output::File(path.into(), format),
+        None => Output::Stdout(format),
+    });
+    job.validate().map_err(|e| e.to_string())?;
+    Ok(job)
+}
+
+fn main() {
+    let registry = Registry::builtin();
+    let mut args = std::env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("list") => {
+            for adapter in registry.list() {
+                println!("{:<16} {}", adapter.name, adapter.help);
+            }
+            return;
+        }
+        Some("help" | "--help" | "-h") => {
+            args.next();
+            match args.next() {
+                None => print!("{}", USAGE),
+                Some(name) => match registry.get(&name) {
+                    Some(adapter) => print!("{}", adapter.help_text()),
+                    None => {
+                        eprintln!("error: unknown adapter {:?}", name);
+                        exit(2);
+                    }
+                },
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let job = match parse_args(args) {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            exit(2);
+        }
+    };
+
+    let result = registry
+        .build(&job)
+        .and_then(|mut runnable| runnable.run(&job));
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_args;
+    use hash_collider::registry::Registry;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(str::to_owned)
+    }
+
+    #[test]
+    fn builds_common_adapters() {
+        let registry = Registry::builtin();
+        for line in [
+            "std --bits 40 --prefix-a hello --threads 2 --stop",
+            "ahash --bits 32 --key0 7 --format json",
+            "md5-ihv --bits 80 --prefix-b world --mask 0xffff",
+            #[cfg(feature = "rustc")]
+            "rustc-typeid --bits 48 --struct-a Left --field-a l",
+        ] {
+            let job = parse_args(args(line)).unwrap();
+            assert!(registry.build(&job).is_ok(), "{}", line);
+        }
+        let job = parse_args(args("rustc-typeid --seconds 5 --struct-b Baz")).unwrap();
+        assert_eq!(job.params.get("struct_b"), Some("Baz"));
+        assert!(parse_args(args("std --bits")).is_err());
+        assert!(parse_args(args("std stray")).is_err());
+    }
+}
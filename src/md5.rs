@@ -1,8 +1,10 @@
-#![allow(clippy::many_single_char_names, dead_code)]
-// use core::convert::TryInto;
+//! The MD5 compression function.
+
+#![allow(clippy::many_single_char_names)]
+
+use crate::block_hash::{le_words, write_words, BlockHash, BLOCK_SIZE};
 
 /// Round constants
-#[cfg(not(feature = "asm"))]
 pub static RC: [u32; 64] = [
     // round 1
     0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
@@ -158,3 +160,54 @@ pub fn compress(state: &mut [u32; 4], input: &[u32; 16]) {
     state[2] = state[2].wrapping_add(c);
     state[3] = state[3].wrapping_add(d);
 }
+
+/// MD5 as a `BlockHash`, its state is the IHV.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Md5;
+
+impl BlockHash for Md5 {
+    const NAME: &'static str = "md5";
+    const BIG_ENDIAN: bool = false;
+
+    type State = [u32; 4];
+    const INIT: Self::State = S0;
+
+    #[inline]
+    fn compress(state: &mut Self::State, block: &[u8; BLOCK_SIZE]) {
+        compress(state, &le_words(block));
+    }
+
+    fn output(state: &Self::State) -> Vec<u8> {
+        Self::output_prefix(state).to_vec()
+    }
+
+    #[inline]
+    fn output_prefix(state: &Self::State) -> [u8; 16] {
+        let mut out = [0_u8; 16];
+        write_words(state, &mut out, false);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Md5;
+    use crate::block_hash::digest;
+    use crate::printer::hex;
+
+    #[test]
+    fn test_vectors() {
+        let cases: [(&[u8], &str); 4] = [
+            (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+            (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+            (b"message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(hex(&digest::<Md5>(data)), expected);
+        }
+    }
+}
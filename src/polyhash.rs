@@ -0,0 +1,318 @@
+//! Equal-hash strings for polynomial hashes like Java's `String.hashCode`.
+//!
+//! A polynomial hash `h = h * base + c` over blocks of equal length is
+//! linear in the characters, so two blocks collide when their character
+//! differences `d` satisfy `sum(d[i] * base^(n-1-i)) = 0 (mod m)`.  Such
+//! short difference vectors are found by lattice reduction, and since equal
+//! length blocks with equal hashes can replace each other anywhere in a
+//! string, `k` blocks give `2^k` strings with the same hash.
+
+use crate::error::{Error, Result};
+use crate::job::Job;
+use crate::registry::Runnable;
+use crate::Stats;
+
+/// `h = h * base + c (mod modulus)` over the bytes of a string, starting from `init`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolyHash {
+    pub base: u64,
+    /// Any modulus in `2..=2^64`, usually a power of two or a prime.
+    pub modulus: u128,
+    pub init: u64,
+}
+
+impl PolyHash {
+    pub fn new(base: u64, modulus: u128) -> Self {
+        assert!(
+            (2..=1 << 64).contains(&modulus),
+            "modulus must be in 2..=2^64"
+        );
+        Self {
+            base,
+            modulus,
+            init: 0,
+        }
+    }
+
+    /// Java's `String.hashCode` for ASCII strings.
+    pub fn java() -> Self {
+        Self::new(31, 1 << 32)
+    }
+
+    pub fn with_init(mut self, init: u64) -> Self {
+        self.init = init;
+        self
+    }
+
+    pub fn hash(&self, data: &[u8]) -> u64 {
+        let base = self.base as u128 % self.modulus;
+        let hash = data.iter().fold(self.init as u128 % self.modulus, |h, &c| {
+            (h * base + c as u128) % self.modulus
+        });
+        hash as u64
+    }
+
+    /// Weights of the characters of an `n` byte block: `base^(n-1-i) mod m`.
+    fn weights(&self, n: usize) -> Vec<u128> {
+        let mut weights = vec![1_u128; n];
+        for ii in (0..n.saturating_sub(1)).rev() {
+            weights[ii] = weights[ii + 1] * (self.base as u128 % self.modulus) % self.modulus;
+        }
+        weights
+    }
+}
+
+/// Parse a charset like `a-zA-Z0-9_`, a `-` which does not form a range is
+/// taken literally.
+pub fn parse_charset(s: &str) -> Result<Vec<u8>> {
+    if !s.is_ascii() {
+        return Err(Error::Invalid(format!("charset {:?} is not ascii", s)));
+    }
+    let bytes = s.as_bytes();
+    let mut charset = Vec::new();
+    let mut ii = 0;
+    while ii < bytes.len() {
+        if ii + 2 < bytes.len() && bytes[ii + 1] == b'-' {
+            let (lo, hi) = (bytes[ii], bytes[ii + 2]);
+            if lo > hi {
+                return Err(Error::Invalid(format!(
+                    "invalid charset range {}-{}",
+                    lo as char, hi as char
+                )));
+            }
+            charset.extend(lo..=hi);
+            ii += 3;
+        } else {
+            charset.push(bytes[ii]);
+            ii += 1;
+        }
+    }
+    charset.sort_unstable();
+    charset.dedup();
+    if charset.is_empty() {
+        return Err(Error::Invalid("empty charset".to_owned()));
+    }
+    Ok(charset)
+}
+
+/// LLL reduction of the rows of `basis` with `delta = 0.99`.
+///
+/// Gram–Schmidt is done in `f64`, which is precise enough for the small
+/// dimensions used here; callers verify the vectors they pick anyway.
+fn lll(basis: &mut [Vec<i128>]) {
+    const DELTA: f64 = 0.99;
+    let n = basis.len();
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+    let gram_schmidt = |basis: &[Vec<i128>]| {
+        let mut ortho: Vec<Vec<f64>> = Vec::with_capacity(n);
+        let mut mu = vec![vec![0.0; n]; n];
+        let mut norms = vec![0.0; n];
+        for ii in 0..n {
+            let row = basis[ii].iter().map(|&x| x as f64).collect::<Vec<_>>();
+            let mut v = row.clone();
+            for jj in 0..ii {
+                mu[ii][jj] = dot(&row, &ortho[jj]) / norms[jj];
+                for (x, o) in v.iter_mut().zip(&ortho[jj]) {
+                    *x -= mu[ii][jj] * o;
+                }
+            }
+            norms[ii] = dot(&v, &v);
+            ortho.push(v);
+        }
+        (mu, norms)
+    };
+
+    let (mut mu, mut norms) = gram_schmidt(basis);
+    let mut k = 1;
+    // Bounded, in case rounding errors keep swapping the same rows.
+    let mut steps = 0;
+    while k < n && steps < 100_000 {
+        steps += 1;
+        for jj in (0..k).rev() {
+            let q = mu[k][jj].round();
+            if q != 0.0 {
+                let (head, tail) = basis.split_at_mut(k);
+                for (x, y) in tail[0].iter_mut().zip(&head[jj]) {
+                    *x -= q as i128 * y;
+                }
+                let (head, tail) = mu.split_at_mut(k);
+                for (x, y) in tail[0][..jj].iter_mut().zip(&head[jj]) {
+                    *x -= q * y;
+                }
+                tail[0][jj] -= q;
+            }
+        }
+        if norms[k] >= (DELTA - mu[k][k - 1] * mu[k][k - 1]) * norms[k - 1] {
+            k += 1;
+        } else {
+            basis.swap(k, k - 1);
+            (mu, norms) = gram_schmidt(basis);
+            k = (k - 1).max(1);
+        }
+    }
+}
+
+/// Two different blocks of equal length and hash, made of bytes in `charset`.
+///
+/// Block lengths up to `max_len` are tried, shortest first.
+pub fn find_block_pair(
+    hash: &PolyHash,
+    charset: &[u8],
+    max_len: usize,
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    // A pair of charset bytes for every possible difference.
+    let mut pairs = std::collections::HashMap::new();
+    for &a in charset {
+        for &b in charset {
+            pairs.entry(a as i128 - b as i128).or_insert((a, b));
+        }
+    }
+    let modulus = hash.modulus as i128;
+
+    for n in 2..=max_len {
+        // The last weight is 1, so the lattice of solutions is spanned by
+        // `e_i - w_i * e_last` and `m * e_last`.
+        let weights = hash.weights(n);
+        let mut basis = (0..n)
+            .map(|ii| {
+                let mut row = vec![0_i128; n];
+                if ii + 1 < n {
+                    row[ii] = 1;
+                    row[n - 1] = -(weights[ii] as i128);
+                } else {
+                    row[n - 1] = modulus;
+                }
+                row
+            })
+            .collect::<Vec<_>>();
+        lll(&mut basis);
+
+        let mut candidates = basis.clone();
+        for (ii, a) in basis.iter().enumerate().take(4) {
+            for b in &basis[ii + 1..basis.len().min(4)] {
+                candidates.push(a.iter().zip(b).map(|(x, y)| x + y).collect());
+                candidates.push(a.iter().zip(b).map(|(x, y)| x - y).collect());
+            }
+        }
+        for diff in candidates {
+            if diff.iter().all(|&d| d == 0) {
+                continue;
+            }
+            let Some(chars) = diff
+                .iter()
+                .map(|d| pairs.get(d))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            let a = chars.iter().map(|&&(a, _)| a).collect::<Vec<_>>();
+            let b = chars.iter().map(|&&(_, b)| b).collect::<Vec<_>>();
+            if hash.with_init(0).hash(&a) == hash.with_init(0).hash(&b) {
+                return Some((a, b));
+            }
+        }
+    }
+    None
+}
+
+/// The `index`-th of the `2^blocks` strings made of `blocks` copies of
+/// `a` or `b`, bit `i` of `index` choosing `b` for block `i`.
+pub fn compose(a: &[u8], b: &[u8], blocks: u32, index: u64) -> Vec<u8> {
+    (0..blocks)
+        .flat_map(|ii| if index >> ii & 1 == 0 { a } else { b })
+        .copied()
+        .collect()
+}
+
+/// A `Runnable` reporting strings with the same hash as the first one.
+pub struct PolyJob {
+    hash: PolyHash,
+    charset: Vec<u8>,
+    max_len: usize,
+}
+
+impl PolyJob {
+    pub fn new(hash: PolyHash, charset: Vec<u8>, max_len: usize) -> Self {
+        Self {
+            hash,
+            charset,
+            max_len,
+        }
+    }
+}
+
+impl Runnable for PolyJob {
+    fn run(&mut self, job: &Job) -> Result<Stats> {
+        let reporter = job.reporter()?;
+        let (a, b) = find_block_pair(&self.hash, &self.charset, self.max_len).ok_or_else(|| {
+            Error::Invalid(format!(
+                "no equal-hash blocks of up to {} bytes over the charset",
+                self.max_len
+            ))
+        })?;
+        let count = job.stop.collisions.unwrap_or(1);
+        let blocks = 64 - count.leading_zeros();
+        let first = compose(&a, &b, blocks, 0);
+        let hash = self.hash.hash(&first);
+        for index in 1..=count {
+            let other = compose(&a, &b, blocks, index);
+            assert_eq!(hash, self.hash.hash(&other));
+            reporter.report_bytes(
+                &first,
+                &other,
+                format_args!(
+                    "found collision! (a, {:?}) (b, {:?}) hash={:x}",
+                    String::from_utf8_lossy(&first),
+                    String::from_utf8_lossy(&other),
+                    hash
+                ),
+            );
+        }
+        Ok(Stats {
+            collisions: count,
+            ..Stats::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compose, find_block_pair, parse_charset, PolyHash};
+
+    #[test]
+    fn java_hash_code() {
+        let java = PolyHash::java();
+        assert_eq!(java.hash(b"hello"), 99162322);
+        assert_eq!(java.hash(b"Aa"), java.hash(b"BB"));
+        // "polygenelubricants".hashCode() == Integer.MIN_VALUE
+        assert_eq!(java.hash(b"polygenelubricants"), 1 << 31);
+    }
+
+    #[test]
+    fn charsets() {
+        assert_eq!(parse_charset("a-c_").unwrap(), b"_abc");
+        assert_eq!(parse_charset("x-").unwrap(), b"-x");
+        assert!(parse_charset("z-a").is_err());
+        assert!(parse_charset("").is_err());
+    }
+
+    #[test]
+    fn equal_hash_strings() {
+        let letters = parse_charset("a-z").unwrap();
+        let cases = [
+            PolyHash::java(),
+            PolyHash::new(31, 1 << 64),
+            PolyHash::new(257, 1_000_000_007).with_init(7),
+            PolyHash::new(0x100000001b3, (1 << 61) - 1),
+        ];
+        for hash in cases {
+            let (a, b) = find_block_pair(&hash, &letters, 24).unwrap();
+            assert_ne!(a, b);
+            assert!(a.iter().chain(&b).all(|c| letters.contains(c)));
+            let expected = hash.hash(&compose(&a, &b, 5, 0));
+            for index in 1..32 {
+                assert_eq!(hash.hash(&compose(&a, &b, 5, index)), expected);
+            }
+        }
+    }
+}
@@ -1,10 +1,42 @@
-use crate::{Collider, HashAdapter};
+use crate::{Collider, HashAdapter, PointCodec};
+use core::fmt;
 use core::ops::ControlFlow;
+use core::str::FromStr;
+use core::sync::atomic::Ordering;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
 
 pub fn stat_printer<A: HashAdapter>(
     interval: u64,
     bits: u8,
     on_found: ControlFlow<(), ()>,
+) -> impl Fn(&Collider<A>) {
+    let stop = StopConditions {
+        collisions: on_found.is_break().then_some(1),
+        ..Default::default()
+    };
+    stat_printer_until(interval, bits, stop, |_| {})
+}
+
+/// When to stop a run, in addition to an adapter returning `Break`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StopConditions {
+    /// Stop once this many collisions are found.
+    pub collisions: Option<u64>,
+    /// Stop after running for this long.
+    pub duration: Option<Duration>,
+}
+
+/// Like `stat_printer`, but stops on any of the `stop` conditions, and calls
+/// `on_tick` after every printed line.
+pub fn stat_printer_until<A: HashAdapter, F: Fn(&Collider<A>)>(
+    interval: u64,
+    bits: u8,
+    stop: StopConditions,
+    on_tick: F,
 ) -> impl Fn(&Collider<A>) {
     move |collider| {
         println!(
@@ -48,9 +80,128 @@ pub fn stat_printer<A: HashAdapter>(
                 e = stats.errors,
             );
 
-            if on_found.is_break() && stats.collisions > 0 {
+            on_tick(collider);
+
+            if !collider.running.load(Ordering::Relaxed)
+                || stop.collisions.is_some_and(|c| stats.collisions >= c)
+                || stop.duration.is_some_and(|d| start.elapsed() >= d)
+            {
                 break;
             }
         }
     }
 }
+
+/// How found collisions are written out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable line, worded by the adapter.
+    #[default]
+    Text,
+    /// One JSON object per line with both points as hex-encoded `PointCodec` bytes.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "unknown output format {:?}, expected text or json",
+                s
+            )),
+        }
+    }
+}
+
+impl OutputFormat {
+    fn write_collision<W: Write>(
+        self,
+        w: &mut W,
+        a: &[u8],
+        b: &[u8],
+        text: fmt::Arguments,
+    ) -> io::Result<()> {
+        match self {
+            Self::Text => writeln!(w, "{}", text),
+            Self::Json => writeln!(w, r#"{{"a":"{}","b":"{}"}}"#, hex(a), hex(b)),
+        }
+    }
+}
+
+enum Sink {
+    Stdout(OutputFormat),
+    File(Mutex<File>, OutputFormat),
+}
+
+/// Where adapters send found collisions: stdout and/or files, each with its
+/// own `OutputFormat`.
+pub struct Reporter {
+    sinks: Vec<Sink>,
+}
+
+impl Default for Reporter {
+    fn default() -> Self {
+        Self::stdout(OutputFormat::Text)
+    }
+}
+
+impl Reporter {
+    /// A reporter without any sinks, which drops every collision.
+    pub fn empty() -> Self {
+        Self { sinks: Vec::new() }
+    }
+
+    pub fn stdout(format: OutputFormat) -> Self {
+        Self {
+            sinks: vec![Sink::Stdout(format)],
+        }
+    }
+
+    pub fn with_stdout(mut self, format: OutputFormat) -> Self {
+        self.sinks.push(Sink::Stdout(format));
+        self
+    }
+
+    /// Append collisions to the file at `path`, creating it if needed.
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P, format: OutputFormat) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.sinks.push(Sink::File(Mutex::new(file), format));
+        Ok(self)
+    }
+
+    /// Report the collision `(a, b)` to all sinks.  `text` is the adapter's
+    /// description of the collision, used by sinks with the text format.
+    pub fn report<P: PointCodec>(&self, a: &P, b: &P, text: fmt::Arguments) {
+        self.report_bytes(&a.to_bytes(), &b.to_bytes(), text)
+    }
+
+    /// Same as `report`, for colliding inputs which are not points.
+    pub fn report_bytes(&self, a: &[u8], b: &[u8], text: fmt::Arguments) {
+        for sink in &self.sinks {
+            let result = match sink {
+                Sink::Stdout(format) => {
+                    format.write_collision(&mut io::stdout().lock(), a, b, text)
+                }
+                Sink::File(file, format) => {
+                    let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                    format.write_collision(&mut *file, a, b, text)
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("failed to report collision: {}", e);
+            }
+        }
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    use core::fmt::Write;
+    bytes.iter().fold(String::new(), |mut out, b| {
+        let _ = write!(out, "{:02x}", b);
+        out
+    })
+}
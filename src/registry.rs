@@ -0,0 +1,1313 @@
+//! Name-based lookup of adapters, so runs can be described by strings on
+//! the command line or in job files.
+
+use crate::adapters::{default_trail_bits, low_mask, TruncatedDigest, TruncatedHasher};
+use crate::block_hash::BlockHash;
+use crate::crc::{Crc, CrcJob, CrcSolver, Template};
+use crate::error::{Error, Result};
+use crate::job::Job;
+use crate::md5::Md5;
+use crate::polyhash::{parse_charset, PolyHash, PolyJob};
+#[cfg(feature = "rustc")]
+use crate::rustc::{
+    crate_ids_in_target, local_rustc_verbose_version, rustc_version, CargoUnit, CrateConfig,
+    CrateIdAdapter, DefPath, DefPathAdapter, GenericTypeIdAdapter, ItemKind, Profile, ProofCrate,
+    StableCrateId, StructTypeIdAdapter, SymbolHashAdapter,
+};
+use crate::sha1::Sha1;
+use crate::sha256::Sha256;
+use crate::siphash::{SipHasher13, SipHasher24};
+use crate::{stat_printer_until, Collider, HashAdapter, PointCodec, Stats};
+use ahash::AHasher;
+use core::cell::Cell;
+use core::fmt::Write;
+use core::hash::Hasher;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+#[cfg(feature = "rustc")]
+use std::path::Path;
+use std::time::Instant;
+
+/// A collision search with its adapter type erased.
+pub trait Runnable {
+    /// Run the search with the threads, stop conditions and checkpoint of `job`.
+    fn run(&mut self, job: &Job) -> Result<Stats>;
+}
+
+/// `Runnable` wrapper around a `Collider`.
+pub struct ColliderJob<A: HashAdapter> {
+    collider: Collider<A>,
+}
+
+impl<A> ColliderJob<A>
+where
+    A: HashAdapter + 'static,
+    A::Point: PointCodec,
+{
+    pub fn new(adapter: A) -> Self {
+        Self {
+            collider: Collider::new(adapter),
+        }
+    }
+
+    pub fn boxed(adapter: A) -> Box<dyn Runnable> {
+        Box::new(Self::new(adapter))
+    }
+}
+
+impl<A> Runnable for ColliderJob<A>
+where
+    A: HashAdapter,
+    A::Point: PointCodec,
+{
+    fn run(&mut self, job: &Job) -> Result<Stats> {
+        let collider = &mut self.collider;
+        if let Some(path) = &job.checkpoint {
+            if path.exists() {
+                let count =
+                    collider.load_trails(BufReader::new(File::open(path)?), &job.fingerprint())?;
+                println!("loaded {} trails from {}", count, path.display());
+            }
+        }
+
+        let last_save = Cell::new(Instant::now());
+        let on_tick = |collider: &Collider<A>| {
+            if job.checkpoint.is_some() && last_save.get().elapsed() >= job.checkpoint_interval {
+                if let Err(e) = save_checkpoint(collider, job) {
+                    eprintln!("failed to save checkpoint: {}", e);
+                }
+                last_save.set(Instant::now());
+            }
+        };
+        collider.run(
+            job.threads,
+            stat_printer_until(job.interval, job.bits as u8, job.stop, on_tick),
+        );
+
+        save_checkpoint(collider, job)?;
+        Ok(collider.report_stats())
+    }
+}
+
+/// Save the trails next to the checkpoint of `job`, if any, and move them
+/// into place, so an interrupted save does not destroy the previous
+/// checkpoint.
+fn save_checkpoint<A>(collider: &Collider<A>, job: &Job) -> Result<()>
+where
+    A: HashAdapter,
+    A::Point: PointCodec,
+{
+    let Some(path) = &job.checkpoint else {
+        return Ok(());
+    };
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    collider.save_trails(BufWriter::new(File::create(&tmp)?), &job.fingerprint())?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// A string parameter accepted by an adapter.
+#[derive(Clone, Copy, Debug)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub help: &'static str,
+    /// Value used when the parameter is not given, if any.
+    pub default: Option<&'static str>,
+}
+
+/// A named adapter constructor.
+#[derive(Clone, Copy)]
+pub struct AdapterInfo {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub params: &'static [ParamInfo],
+    /// Supported point widths.
+    pub bits: (u32, u32),
+    /// Build the adapter from the parameters, `bits`, `trail_mask` and
+    /// outputs of the job.  The parameter names and point width are checked
+    /// before this is called.
+    pub build: fn(&Job) -> Result<Box<dyn Runnable>>,
+}
+
+impl AdapterInfo {
+    /// Multi-line description of the adapter and its parameters.
+    pub fn help_text(&self) -> String {
+        let mut text = format!(
+            "{}: {}\n    bits: {}..={}\n",
+            self.name, self.help, self.bits.0, self.bits.1
+        );
+        for param in self.params {
+            let _ = write!(
+                text,
+                "    --{:<16} {}",
+                param.name.replace('_', "-"),
+                param.help
+            );
+            if let Some(default) = param.default {
+                let _ = write!(text, " (default {:?})", default);
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Adapters known by name.
+#[derive(Clone, Default)]
+pub struct Registry {
+    adapters: Vec<AdapterInfo>,
+}
+
+impl Registry {
+    /// A registry without any adapters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with all adapters shipped with the crate.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(STD);
+        registry.register(AHASH);
+        registry.register(SIPHASH13);
+        registry.register(SIPHASH24);
+        registry.register(MD5_IHV);
+        registry.register(SHA1);
+        registry.register(SHA256);
+        registry.register(CRC);
+        registry.register(POLYHASH);
+        #[cfg(feature = "rustc")]
+        registry.register(RUSTC_DEF_PATH);
+        #[cfg(feature = "rustc")]
+        registry.register(RUSTC_TYPE_ID);
+        #[cfg(feature = "rustc")]
+        registry.register(RUSTC_GENERIC_TYPE_ID);
+        #[cfg(feature = "rustc")]
+        registry.register(RUSTC_SYMBOL);
+        #[cfg(feature = "rustc")]
+        registry.register(RUSTC_CRATE_ID);
+        registry
+    }
+
+    /// Add an adapter, replacing any adapter with the same name.
+    pub fn register(&mut self, info: AdapterInfo) {
+        self.adapters.retain(|a| a.name != info.name);
+        self.adapters.push(info);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AdapterInfo> {
+        self.adapters.iter().find(|a| a.name == name)
+    }
+
+    pub fn list(&self) -> impl Iterator<Item = &AdapterInfo> {
+        self.adapters.iter()
+    }
+
+    /// Check the adapter, its parameters and point width, and build it.
+    pub fn build(&self, job: &Job) -> Result<Box<dyn Runnable>> {
+        let info = self
+            .get(&job.adapter)
+            .ok_or_else(|| Error::Invalid(format!("unknown adapter {:?}", job.adapter)))?;
+        let known = info.params.iter().map(|p| p.name).collect::<Vec<_>>();
+        job.params.check_known(&known)?;
+        if !(info.bits.0..=info.bits.1).contains(&job.bits) {
+            return Err(Error::Invalid(format!(
+                "adapter {} needs bits in {}..={}",
+                info.name, info.bits.0, info.bits.1
+            )));
+        }
+        (info.build)(job)
+    }
+}
+
+const PREFIX_PARAMS: [ParamInfo; 2] = [
+    ParamInfo {
+        name: "prefix_a",
+        help: "data hashed before the first point",
+        default: Some("a"),
+    },
+    ParamInfo {
+        name: "prefix_b",
+        help: "data hashed before the second point",
+        default: Some("b"),
+    },
+];
+
+fn truncated_hasher<H>(job: &Job, hasher: H) -> Result<Box<dyn Runnable>>
+where
+    H: Hasher + Clone + Sync + 'static,
+{
+    let mut prefix_a = hasher.clone();
+    prefix_a.write(job.params.get_or("prefix_a", "a").as_bytes());
+    let mut prefix_b = hasher;
+    prefix_b.write(job.params.get_or("prefix_b", "b").as_bytes());
+    let adapter = TruncatedHasher::new(prefix_a, prefix_b, job.bits, job.trail_mask_or_default())
+        .with_reporter(job.reporter()?);
+    Ok(ColliderJob::boxed(adapter))
+}
+
+const STD: AdapterInfo = AdapterInfo {
+    name: "std",
+    help: "truncated std DefaultHasher (currently SipHash-1-3 with zero keys)",
+    params: &PREFIX_PARAMS,
+    bits: (1, 64),
+    build: |job| truncated_hasher(job, DefaultHasher::new()),
+};
+
+const AHASH: AdapterInfo = AdapterInfo {
+    name: "ahash",
+    help: "truncated AHasher",
+    params: &[
+        PREFIX_PARAMS[0],
+        PREFIX_PARAMS[1],
+        ParamInfo {
+            name: "key0",
+            help: "first AHasher key",
+            default: Some("1234"),
+        },
+        ParamInfo {
+            name: "key1",
+            help: "second AHasher key",
+            default: Some("5678"),
+        },
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let key0 = job.params.parse_or("key0", 1234)?;
+        let key1 = job.params.parse_or("key1", 5678)?;
+        truncated_hasher(job, AHasher::new_with_keys(key0, key1))
+    },
+};
+
+/// A 64-bit key, decimal or hexadecimal with a `0x` prefix.
+fn key_param(job: &Job, name: &str) -> Result<u64> {
+    let value = job.params.get_or(name, "0");
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|e| Error::Invalid(format!("invalid value for parameter {}: {}", name, e)))
+}
+
+/// A hexadecimal value, with or without a `0x` prefix, `None` if not given.
+fn hex_param(job: &Job, name: &str) -> Result<Option<u64>> {
+    let Some(value) = job.params.get(name) else {
+        return Ok(None);
+    };
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    u64::from_str_radix(hex, 16)
+        .map(Some)
+        .map_err(|e| Error::Invalid(format!("invalid hex value for parameter {}: {}", name, e)))
+}
+
+const SIPHASH_PARAMS: [ParamInfo; 4] = [
+    PREFIX_PARAMS[0],
+    PREFIX_PARAMS[1],
+    ParamInfo {
+        name: "key0",
+        help: "first SipHash key, decimal or 0x-prefixed hex",
+        default: Some("0"),
+    },
+    ParamInfo {
+        name: "key1",
+        help: "second SipHash key, decimal or 0x-prefixed hex",
+        default: Some("0"),
+    },
+];
+
+const SIPHASH13: AdapterInfo = AdapterInfo {
+    name: "siphash13",
+    help: "truncated SipHash-1-3 with explicit keys",
+    params: &SIPHASH_PARAMS,
+    bits: (1, 64),
+    build: |job| {
+        let hasher = SipHasher13::new_with_keys(key_param(job, "key0")?, key_param(job, "key1")?);
+        truncated_hasher(job, hasher)
+    },
+};
+
+const SIPHASH24: AdapterInfo = AdapterInfo {
+    name: "siphash24",
+    help: "truncated SipHash-2-4 with explicit keys",
+    params: &SIPHASH_PARAMS,
+    bits: (1, 64),
+    build: |job| {
+        let hasher = SipHasher24::new_with_keys(key_param(job, "key0")?, key_param(job, "key1")?);
+        truncated_hasher(job, hasher)
+    },
+};
+
+/// Prefix given either inline as `name` or as the contents of `name_file`.
+fn prefix_bytes(job: &Job, name: &str, default: &str) -> Result<Vec<u8>> {
+    let file_param = format!("{}_file", name);
+    match (job.params.get(name), job.params.get(&file_param)) {
+        (Some(_), Some(_)) => Err(Error::Invalid(format!(
+            "parameters {} and {} are mutually exclusive",
+            name, file_param
+        ))),
+        (None, Some(path)) => Ok(std::fs::read(path)?),
+        (value, None) => Ok(value.unwrap_or(default).as_bytes().to_vec()),
+    }
+}
+
+/// Distinguishes points by their leading zero bits, for the adapters that
+/// cannot take an arbitrary mask.
+const TRAIL_BITS_PARAM: ParamInfo = ParamInfo {
+    name: "trail_bits",
+    help: "leading zero bits of a distinguishing point, instead of a trail mask of the lowest bits",
+    default: None,
+};
+
+/// The `TRAIL_BITS_PARAM`, or the width of a trail mask made of the lowest
+/// bits, or the default for the point width.
+fn leading_trail_bits(job: &Job) -> Result<u32> {
+    let trail_bits = match (job.params.get("trail_bits"), job.trail_mask) {
+        (Some(_), Some(_)) => {
+            return Err(Error::Invalid(
+                "parameter trail_bits and the trail mask are mutually exclusive".to_owned(),
+            ))
+        }
+        (Some(_), None) => job.params.parse_or("trail_bits", 0)?,
+        (None, Some(mask)) if mask == low_mask(mask.count_ones()) => mask.count_ones(),
+        (None, Some(mask)) => {
+            return Err(Error::Invalid(format!(
+                "adapter {} counts leading zero bits, the trail mask {:#x} must be a run of the lowest bits, or use trail_bits",
+                job.adapter, mask
+            )))
+        }
+        (None, None) => return Ok(default_trail_bits(job.bits)),
+    };
+    if trail_bits > job.bits {
+        return Err(Error::Invalid(format!(
+            "trail_bits {} does not fit into {} bits",
+            trail_bits, job.bits
+        )));
+    }
+    Ok(trail_bits)
+}
+
+fn truncated_digest<H, const BITS: usize>(job: &Job) -> Result<Box<dyn Runnable>>
+where
+    H: BlockHash + 'static,
+{
+    let prefix_a = prefix_bytes(job, "prefix_a", "a")?;
+    let prefix_b = prefix_bytes(job, "prefix_b", "b")?;
+    let trail_bits = leading_trail_bits(job)?;
+    let mut adapter = TruncatedDigest::<H, BITS>::new(&prefix_a, &prefix_b)
+        .with_trail_bits(trail_bits)
+        .with_reporter(job.reporter()?);
+    if let Some(dir) = job.params.get("out_dir") {
+        adapter = adapter.with_out_dir(dir);
+    }
+    Ok(ColliderJob::boxed(adapter))
+}
+
+/// Build a `TruncatedDigest` for one of the supported point widths.
+fn digest_adapter<H: BlockHash + 'static>(job: &Job) -> Result<Box<dyn Runnable>> {
+    match job.bits {
+        64 => truncated_digest::<H, 64>(job),
+        80 => truncated_digest::<H, 80>(job),
+        96 => truncated_digest::<H, 96>(job),
+        bits => Err(Error::Invalid(format!(
+            "adapter {} supports bits 64, 80 or 96, not {}",
+            job.adapter, bits
+        ))),
+    }
+}
+
+const DIGEST_PARAMS: [ParamInfo; 6] = [
+    PREFIX_PARAMS[0],
+    PREFIX_PARAMS[1],
+    ParamInfo {
+        name: "prefix_a_file",
+        help: "file used instead of prefix_a",
+        default: None,
+    },
+    ParamInfo {
+        name: "prefix_b_file",
+        help: "file used instead of prefix_b",
+        default: None,
+    },
+    ParamInfo {
+        name: "out_dir",
+        help: "directory the colliding message pairs are written to",
+        default: None,
+    },
+    TRAIL_BITS_PARAM,
+];
+
+const MD5_IHV: AdapterInfo = AdapterInfo {
+    name: "md5-ihv",
+    help: "truncated MD5 of a zero-padded prefix followed by the point, bits 64, 80 or 96",
+    params: &DIGEST_PARAMS,
+    bits: (64, 96),
+    build: digest_adapter::<Md5>,
+};
+
+const SHA1: AdapterInfo = AdapterInfo {
+    name: "sha1",
+    help: "truncated SHA-1 of a zero-padded prefix followed by the point, bits 64, 80 or 96",
+    params: &DIGEST_PARAMS,
+    bits: (64, 96),
+    build: digest_adapter::<Sha1>,
+};
+
+const SHA256: AdapterInfo = AdapterInfo {
+    name: "sha256",
+    help: "truncated SHA-256 of a zero-padded prefix followed by the point, bits 64, 80 or 96",
+    params: &DIGEST_PARAMS,
+    bits: (64, 96),
+    build: digest_adapter::<Sha256>,
+};
+
+const CRC: AdapterInfo = AdapterInfo {
+    name: "crc",
+    help: "CRC collisions or preimages solved over GF(2) instead of searched, bits <= CRC width",
+    params: &[
+        ParamInfo {
+            name: "preset",
+            help: "crc32, crc32c, crc32-bzip2 or crc64-xz",
+            default: Some("crc32"),
+        },
+        PREFIX_PARAMS[0],
+        PREFIX_PARAMS[1],
+        ParamInfo {
+            name: "prefix_a_file",
+            help: "file used instead of prefix_a",
+            default: None,
+        },
+        ParamInfo {
+            name: "prefix_b_file",
+            help: "file used instead of prefix_b",
+            default: None,
+        },
+        ParamInfo {
+            name: "free_bytes",
+            help: "number of solved bytes appended to each prefix (default: bits / 8 + 1)",
+            default: None,
+        },
+        ParamInfo {
+            name: "target",
+            help: "hex checksum, solve for a preimage of it after prefix_a instead",
+            default: None,
+        },
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let preset = job.params.get_or("preset", "crc32");
+        let crc = Crc::by_name(preset)
+            .ok_or_else(|| Error::Invalid(format!("unknown CRC preset {:?}", preset)))?;
+        if job.bits > crc.width {
+            return Err(Error::Invalid(format!(
+                "{} is only {} bits wide",
+                crc.name, crc.width
+            )));
+        }
+        let free = job
+            .params
+            .parse_or("free_bytes", job.bits as usize / 8 + 1)?;
+        let a = Template::new(prefix_bytes(job, "prefix_a", "a")?).with_free_suffix(free);
+        let b = Template::new(prefix_bytes(job, "prefix_b", "b")?).with_free_suffix(free);
+        let target = hex_param(job, "target")?;
+        let solver = CrcSolver::new(crc, job.bits);
+        Ok(Box::new(CrcJob::new(solver, a, b, target)))
+    },
+};
+
+const POLYHASH: AdapterInfo = AdapterInfo {
+    name: "polyhash",
+    help: "equal-hash strings for h = h * base + c mod 2^bits, or mod --modulus",
+    params: &[
+        ParamInfo {
+            name: "base",
+            help: "multiplier of the hash",
+            default: Some("31"),
+        },
+        ParamInfo {
+            name: "modulus",
+            help: "modulus used instead of 2^bits, such as a prime",
+            default: None,
+        },
+        ParamInfo {
+            name: "init",
+            help: "hash of the empty string",
+            default: Some("0"),
+        },
+        ParamInfo {
+            name: "charset",
+            help: "allowed characters, with ranges like a-z",
+            default: Some("a-zA-Z"),
+        },
+        ParamInfo {
+            name: "max_len",
+            help: "longest equal-hash block to search for",
+            default: Some("32"),
+        },
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let modulus = match job.params.get("modulus") {
+            Some(_) => job.params.parse_or("modulus", 0_u64)? as u128,
+            None => 1 << job.bits,
+        };
+        if modulus < 2 {
+            return Err(Error::Invalid("modulus must be at least 2".to_owned()));
+        }
+        let hash = PolyHash::new(job.params.parse_or("base", 31)?, modulus)
+            .with_init(job.params.parse_or("init", 0)?);
+        let charset = parse_charset(job.params.get_or("charset", "a-zA-Z"))?;
+        let max_len = job.params.parse_or("max_len", 32)?;
+        Ok(Box::new(PolyJob::new(hash, charset, max_len)))
+    },
+};
+
+#[cfg(feature = "rustc")]
+const RUSTC_CRATE_PARAMS: [ParamInfo; 7] = [
+    ParamInfo {
+        name: "profile",
+        help: "compiler to target, 1.64-nightly, 1.72-dev or 1.95",
+        default: Some("1.72-dev"),
+    },
+    ParamInfo {
+        name: "crate_name",
+        help: "name of the crate",
+        default: Some("playground"),
+    },
+    ParamInfo {
+        name: "crate_type",
+        help: "bin or lib",
+        default: Some("bin"),
+    },
+    ParamInfo {
+        name: "metadata",
+        help: "comma-separated -C metadata values",
+        default: Some(""),
+    },
+    ParamInfo {
+        name: "rustc_version",
+        help: "version string rustc was built with, defaults to the one of the profile",
+        default: None,
+    },
+    ParamInfo {
+        name: "package_version",
+        help: "compute metadata like cargo does for a dev build of this package version, with the local rustc and its version",
+        default: None,
+    },
+    ParamInfo {
+        name: "cargo_target_dir",
+        help: "read the crate id from the incremental directories of a dev build in this target directory",
+        default: None,
+    },
+];
+
+/// The profile of the adapters hashing structs, which need a compiler whose
+/// ADT layout is modelled.
+#[cfg(feature = "rustc")]
+const RUSTC_ADT_PROFILE: ParamInfo = ParamInfo {
+    name: "profile",
+    help: "compiler to target, one whose struct layout is modelled: 1.95, or 1.64-nightly for single-field structs",
+    default: Some("1.95"),
+};
+
+#[cfg(feature = "rustc")]
+const RUSTC_SYMBOL_PROFILE: ParamInfo = ParamInfo {
+    name: "profile",
+    help: "compiler to target, one whose symbol hashing is modelled: 1.95",
+    default: Some("1.95"),
+};
+
+#[cfg(feature = "rustc")]
+const RUSTC_PROOF_PARAMS: [ParamInfo; 2] = [
+    ParamInfo {
+        name: "proof_dir",
+        help: "directory cargo projects asserting equal TypeIds are written to, needs bits 64",
+        default: None,
+    },
+    ParamInfo {
+        name: "verify_proof",
+        help: "build and run the proofs with the local rustc, true or false",
+        default: Some("false"),
+    },
+];
+
+/// The compiler profile from the `RUSTC_CRATE_PARAMS`, `default` if the job
+/// names none.
+#[cfg(feature = "rustc")]
+fn rustc_profile(job: &Job, default: &str) -> Result<&'static Profile> {
+    let name = job.params.get_or("profile", default);
+    Profile::by_name(name)
+        .ok_or_else(|| Error::Invalid(format!("unknown rustc profile {:?}", name)))
+}
+
+/// Whether the `crate_type` of the `RUSTC_CRATE_PARAMS` is an executable.
+#[cfg(feature = "rustc")]
+fn crate_is_exe(job: &Job) -> Result<bool> {
+    parse_crate_type("crate_type", job.params.get_or("crate_type", "bin"))
+}
+
+/// Whether the crate type `value` of the parameter `name` is an executable.
+#[cfg(feature = "rustc")]
+fn parse_crate_type(name: &str, value: &str) -> Result<bool> {
+    match value {
+        "bin" => Ok(true),
+        "lib" => Ok(false),
+        other => Err(Error::Invalid(format!(
+            "{} must be bin or lib, not {:?}",
+            name, other
+        ))),
+    }
+}
+
+/// The `-C metadata` values from the `RUSTC_CRATE_PARAMS`, computed like
+/// cargo does when `package_version` is given.
+#[cfg(feature = "rustc")]
+fn crate_metadata(job: &Job, rustc_verbose: fn() -> Result<String>) -> Result<Vec<String>> {
+    let crate_name = job.params.get_or("crate_name", "playground");
+    Ok(match job.params.get("package_version") {
+        Some(version) => {
+            let mut unit = CargoUnit::new(crate_name, version, &rustc_verbose()?);
+            if !crate_is_exe(job)? {
+                unit = unit.with_lib();
+            }
+            vec![format!("{:016x}", unit.metadata()?.c_metadata)]
+        }
+        None => job
+            .params
+            .get_or("metadata", "")
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned)
+            .collect(),
+    })
+}
+
+/// Pairs of `RUSTC_CRATE_PARAMS` where the second one makes the first one
+/// meaningless.
+#[cfg(feature = "rustc")]
+const CONFLICTING_CRATE_PARAMS: [(&str, &str); 6] = [
+    ("metadata", "package_version"),
+    ("rustc_version", "package_version"),
+    ("metadata", "cargo_target_dir"),
+    ("crate_type", "cargo_target_dir"),
+    ("rustc_version", "cargo_target_dir"),
+    ("package_version", "cargo_target_dir"),
+];
+
+/// The crate id from the `RUSTC_CRATE_PARAMS`, hashed for `profile`.
+///
+/// With `package_version`, the metadata is computed for the local rustc, so
+/// its version is hashed rather than the one of the profile.
+#[cfg(feature = "rustc")]
+fn stable_crate_id(job: &Job, profile: &'static Profile) -> Result<StableCrateId> {
+    crate_id_with_rustc(job, profile, local_rustc_verbose_version)
+}
+
+/// `stable_crate_id`, with `rustc_verbose` giving the `rustc -vV` output of
+/// the local compiler.
+#[cfg(feature = "rustc")]
+fn crate_id_with_rustc(
+    job: &Job,
+    profile: &'static Profile,
+    rustc_verbose: fn() -> Result<String>,
+) -> Result<StableCrateId> {
+    for (ignored, param) in CONFLICTING_CRATE_PARAMS {
+        if job.params.get(ignored).is_some() && job.params.get(param).is_some() {
+            return Err(Error::Invalid(format!(
+                "parameters {} and {} are mutually exclusive",
+                ignored, param
+            )));
+        }
+    }
+    let is_exe = crate_is_exe(job)?;
+    let crate_name = job.params.get_or("crate_name", "playground");
+    if let Some(dir) = job.params.get("cargo_target_dir") {
+        return match crate_ids_in_target(Path::new(dir), crate_name)?[..] {
+            [hash] => Ok(StableCrateId { hash, profile }),
+            [] => Err(Error::Invalid(format!(
+                "no incremental directory of crate {} in {}",
+                crate_name, dir
+            ))),
+            _ => Err(Error::Invalid(format!(
+                "several crate ids of crate {} in {}, clean it and build once",
+                crate_name, dir
+            ))),
+        };
+    }
+    let rustc_version = match job.params.get("package_version") {
+        Some(_) => rustc_version(&rustc_verbose()?)?,
+        None => job
+            .params
+            .get_or("rustc_version", profile.rustc_version)
+            .to_owned(),
+    };
+    Ok(StableCrateId::new(
+        crate_name,
+        is_exe,
+        crate_metadata(job, rustc_verbose)?,
+        &rustc_version,
+        profile,
+    ))
+}
+
+/// The template of the proof crates for the `RUSTC_CRATE_PARAMS`.
+///
+/// Proofs are only written for collisions of the full hash, which narrower
+/// points never give.
+#[cfg(feature = "rustc")]
+fn proof_crate(job: &Job, profile: &Profile) -> Result<ProofCrate> {
+    if job.bits != 64 {
+        return Err(Error::Invalid(format!(
+            "proofs need bits 64, collisions of {} bits are not collisions of the full hash",
+            job.bits
+        )));
+    }
+    if !crate_is_exe(job)? {
+        return Err(Error::Invalid("proofs need a bin crate".to_owned()));
+    }
+    if job.params.get("cargo_target_dir").is_some() {
+        return Err(Error::Invalid(
+            "proofs need the metadata or package_version, not cargo_target_dir".to_owned(),
+        ));
+    }
+    let proof = ProofCrate::new(
+        job.params.get_or("crate_name", "playground"),
+        crate_metadata(job, local_rustc_verbose_version)?,
+        profile.playground_wrapper,
+    );
+    Ok(match job.params.get("package_version") {
+        Some(version) => proof.with_package_version(version),
+        None => proof,
+    })
+}
+
+/// `proof_crate` for adapters colliding `TypeId`s, whose proofs need the
+/// whole `TypeId` in the point.
+#[cfg(feature = "rustc")]
+fn type_id_proof_crate(job: &Job, profile: &Profile) -> Result<ProofCrate> {
+    if profile.type_id_bits > 64 {
+        return Err(Error::Invalid(format!(
+            "proofs need the whole TypeId in the point, profile {} has {}-bit TypeIds",
+            profile.name, profile.type_id_bits
+        )));
+    }
+    proof_crate(job, profile)
+}
+
+/// An item given as `keyword name`, like `mod foo_`.
+#[cfg(feature = "rustc")]
+fn item_param<'a>(job: &'a Job, name: &str, default: &'a str) -> Result<(ItemKind, &'a str)> {
+    let value = job.params.get_or(name, default);
+    value
+        .split_once(' ')
+        .and_then(|(keyword, prefix)| Some((ItemKind::from_keyword(keyword)?, prefix.trim())))
+        .ok_or_else(|| {
+            Error::Invalid(format!(
+                "parameter {} must be an item keyword and a name prefix, like \"mod foo_\", not {:?}",
+                name, value
+            ))
+        })
+}
+
+#[cfg(feature = "rustc")]
+const RUSTC_DEF_PATH: AdapterInfo = AdapterInfo {
+    name: "rustc-def-path",
+    help: "colliding DefPathHashes of two items named by a prefix and the point in hex",
+    params: &[
+        RUSTC_CRATE_PARAMS[0],
+        RUSTC_CRATE_PARAMS[1],
+        RUSTC_CRATE_PARAMS[2],
+        RUSTC_CRATE_PARAMS[3],
+        RUSTC_CRATE_PARAMS[4],
+        RUSTC_CRATE_PARAMS[5],
+        RUSTC_CRATE_PARAMS[6],
+        ParamInfo {
+            name: "parent",
+            help: "path of the items inside the snippet, like \"mod inner\"",
+            default: Some(""),
+        },
+        ParamInfo {
+            name: "item_a",
+            help: "first item kind and name prefix",
+            default: Some("mod foo_"),
+        },
+        ParamInfo {
+            name: "item_b",
+            help: "second item kind and name prefix",
+            default: Some("mod bar_"),
+        },
+        RUSTC_PROOF_PARAMS[0],
+        RUSTC_PROOF_PARAMS[1],
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let profile = rustc_profile(job, "1.72-dev")?;
+        let crate_id = stable_crate_id(job, profile)?;
+        let mut parent = profile.snippet_root(crate_id);
+        let inner = DefPath::parse(crate_id, job.params.get_or("parent", ""))?.data;
+        parent.data.extend(inner.iter().cloned());
+        let mut adapter = DefPathAdapter::new(
+            &parent,
+            item_param(job, "item_a", "mod foo_")?,
+            item_param(job, "item_b", "mod bar_")?,
+        )
+        .with_bits(job.bits, job.trail_mask_or_default())
+        .with_reporter(job.reporter()?);
+        if let Some(dir) = job.params.get("proof_dir") {
+            adapter = adapter.with_proof_dir(dir, proof_crate(job, profile)?, &inner)?;
+            if job.params.parse_or("verify_proof", false)? {
+                adapter = adapter.with_proof_verification();
+            }
+        }
+        Ok(ColliderJob::boxed(adapter))
+    },
+};
+
+#[cfg(feature = "rustc")]
+const RUSTC_TYPE_ID: AdapterInfo = AdapterInfo {
+    name: "rustc-typeid",
+    help: "colliding TypeIds of two structs with one field named by a prefix and the point in hex",
+    params: &[
+        RUSTC_ADT_PROFILE,
+        RUSTC_CRATE_PARAMS[1],
+        RUSTC_CRATE_PARAMS[2],
+        RUSTC_CRATE_PARAMS[3],
+        RUSTC_CRATE_PARAMS[4],
+        RUSTC_CRATE_PARAMS[5],
+        RUSTC_CRATE_PARAMS[6],
+        ParamInfo {
+            name: "parent",
+            help: "path of the structs inside the snippet, like \"mod inner\"",
+            default: Some(""),
+        },
+        ParamInfo {
+            name: "struct_a",
+            help: "name of the first struct",
+            default: Some("Foo"),
+        },
+        ParamInfo {
+            name: "field_a",
+            help: "name prefix of the field of the first struct",
+            default: Some("x"),
+        },
+        ParamInfo {
+            name: "struct_b",
+            help: "name of the second struct",
+            default: Some("Bar"),
+        },
+        ParamInfo {
+            name: "field_b",
+            help: "name prefix of the field of the second struct",
+            default: Some("x"),
+        },
+        RUSTC_PROOF_PARAMS[0],
+        RUSTC_PROOF_PARAMS[1],
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let profile = rustc_profile(job, "1.95")?;
+        let crate_id = stable_crate_id(job, profile)?;
+        let mut parent = profile.snippet_root(crate_id);
+        let inner = DefPath::parse(crate_id, job.params.get_or("parent", ""))?.data;
+        parent.data.extend(inner.iter().cloned());
+        let mut adapter = StructTypeIdAdapter::new(
+            &parent,
+            (
+                job.params.get_or("struct_a", "Foo"),
+                job.params.get_or("field_a", "x"),
+            ),
+            (
+                job.params.get_or("struct_b", "Bar"),
+                job.params.get_or("field_b", "x"),
+            ),
+        )?
+        .with_bits(job.bits, job.trail_mask_or_default())
+        .with_reporter(job.reporter()?);
+        if let Some(dir) = job.params.get("proof_dir") {
+            adapter = adapter.with_proof_dir(dir, type_id_proof_crate(job, profile)?, &inner)?;
+            if job.params.parse_or("verify_proof", false)? {
+                adapter = adapter.with_proof_verification();
+            }
+        }
+        Ok(ColliderJob::boxed(adapter))
+    },
+};
+
+#[cfg(feature = "rustc")]
+const RUSTC_GENERIC_TYPE_ID: AdapterInfo = AdapterInfo {
+    name: "rustc-generic-type-id",
+    help: "colliding TypeIds of two generic structs instantiated with structs named by a prefix and the point in hex",
+    params: &[
+        RUSTC_ADT_PROFILE,
+        RUSTC_CRATE_PARAMS[1],
+        RUSTC_CRATE_PARAMS[2],
+        RUSTC_CRATE_PARAMS[3],
+        RUSTC_CRATE_PARAMS[4],
+        RUSTC_CRATE_PARAMS[5],
+        RUSTC_CRATE_PARAMS[6],
+        ParamInfo {
+            name: "parent",
+            help: "path of the items inside the snippet, like \"mod inner\"",
+            default: Some(""),
+        },
+        ParamInfo {
+            name: "generic_a",
+            help: "first generic struct",
+            default: Some("Foo"),
+        },
+        ParamInfo {
+            name: "arg_a",
+            help: "name prefix of the type argument of the first struct",
+            default: Some("A"),
+        },
+        ParamInfo {
+            name: "generic_b",
+            help: "second generic struct",
+            default: Some("Bar"),
+        },
+        ParamInfo {
+            name: "arg_b",
+            help: "name prefix of the type argument of the second struct",
+            default: Some("B"),
+        },
+        RUSTC_PROOF_PARAMS[0],
+        RUSTC_PROOF_PARAMS[1],
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let profile = rustc_profile(job, "1.95")?;
+        let crate_id = stable_crate_id(job, profile)?;
+        let mut parent = profile.snippet_root(crate_id);
+        let inner = DefPath::parse(crate_id, job.params.get_or("parent", ""))?.data;
+        parent.data.extend(inner.iter().cloned());
+        let mut adapter = GenericTypeIdAdapter::new(
+            &parent,
+            (
+                job.params.get_or("generic_a", "Foo"),
+                job.params.get_or("arg_a", "A"),
+            ),
+            (
+                job.params.get_or("generic_b", "Bar"),
+                job.params.get_or("arg_b", "B"),
+            ),
+        )?
+        .with_bits(job.bits, job.trail_mask_or_default())
+        .with_reporter(job.reporter()?);
+        if let Some(dir) = job.params.get("proof_dir") {
+            adapter = adapter.with_proof_dir(dir, type_id_proof_crate(job, profile)?, &inner)?;
+            if job.params.parse_or("verify_proof", false)? {
+                adapter = adapter.with_proof_verification();
+            }
+        }
+        Ok(ColliderJob::boxed(adapter))
+    },
+};
+
+#[cfg(feature = "rustc")]
+const RUSTC_SYMBOL: AdapterInfo = AdapterInfo {
+    name: "rustc-symbol",
+    help: "colliding legacy symbol hashes of two functions named by a prefix and the point in hex",
+    params: &[
+        RUSTC_SYMBOL_PROFILE,
+        RUSTC_CRATE_PARAMS[1],
+        RUSTC_CRATE_PARAMS[2],
+        RUSTC_CRATE_PARAMS[3],
+        RUSTC_CRATE_PARAMS[4],
+        RUSTC_CRATE_PARAMS[5],
+        RUSTC_CRATE_PARAMS[6],
+        ParamInfo {
+            name: "parent",
+            help: "path of the functions inside the snippet, like \"mod inner\"",
+            default: Some(""),
+        },
+        ParamInfo {
+            name: "fn_a",
+            help: "name prefix of the first function",
+            default: Some("foo_"),
+        },
+        ParamInfo {
+            name: "fn_b",
+            help: "name prefix of the second function",
+            default: Some("bar_"),
+        },
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let profile = rustc_profile(job, "1.95")?;
+        let crate_id = stable_crate_id(job, profile)?;
+        let mut parent = profile.snippet_root(crate_id);
+        parent
+            .data
+            .extend(DefPath::parse(crate_id, job.params.get_or("parent", ""))?.data);
+        let adapter = SymbolHashAdapter::new(
+            job.params.get_or("crate_name", "playground"),
+            &parent,
+            job.params.get_or("fn_a", "foo_"),
+            job.params.get_or("fn_b", "bar_"),
+        )?
+        .with_bits(job.bits, job.trail_mask_or_default())
+        .with_reporter(job.reporter()?);
+        Ok(ColliderJob::boxed(adapter))
+    },
+};
+
+#[cfg(feature = "rustc")]
+const RUSTC_CRATE_ID: AdapterInfo = AdapterInfo {
+    name: "rustc-crate-id",
+    help: "colliding StableCrateIds of two crates passed -C metadata values named by a prefix and the point in hex",
+    params: &[
+        RUSTC_CRATE_PARAMS[0],
+        RUSTC_CRATE_PARAMS[1],
+        RUSTC_CRATE_PARAMS[2],
+        RUSTC_CRATE_PARAMS[4],
+        ParamInfo {
+            name: "crate_name_b",
+            help: "name of the second crate, defaults to crate_name",
+            default: None,
+        },
+        ParamInfo {
+            name: "crate_type_b",
+            help: "bin or lib, defaults to crate_type",
+            default: None,
+        },
+        ParamInfo {
+            name: "metadata_a",
+            help: "-C metadata prefix of the first crate",
+            default: Some("a"),
+        },
+        ParamInfo {
+            name: "metadata_b",
+            help: "-C metadata prefix of the second crate",
+            default: Some("b"),
+        },
+    ],
+    bits: (1, 64),
+    build: |job| {
+        let profile = rustc_profile(job, "1.72-dev")?;
+        let crate_name = job.params.get_or("crate_name", "playground");
+        let crate_type = job.params.get_or("crate_type", "bin");
+        let crate_a = CrateConfig::new(
+            crate_name,
+            parse_crate_type("crate_type", crate_type)?,
+            job.params.get_or("metadata_a", "a"),
+        );
+        let crate_b = CrateConfig::new(
+            job.params.get_or("crate_name_b", crate_name),
+            parse_crate_type("crate_type_b", job.params.get_or("crate_type_b", crate_type))?,
+            job.params.get_or("metadata_b", "b"),
+        );
+        let adapter = CrateIdAdapter::new(profile, crate_a, crate_b)
+            .with_rustc_version(job.params.get_or("rustc_version", profile.rustc_version))
+            .with_bits(job.bits, job.trail_mask_or_default())
+            .with_reporter(job.reporter()?);
+        Ok(ColliderJob::boxed(adapter))
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use crate::job::Job;
+
+    /// Assert that `registry` rejects `adapter` with `bits` and `param` set
+    /// to `value`.
+    fn assert_rejects(adapter: &str, bits: u32, param: &str, value: &str) {
+        let mut job = Job::new(adapter, bits);
+        job.params.insert(param, value);
+        assert!(
+            Registry::builtin().build(&job).is_err(),
+            "accepted {:?}",
+            job
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        let registry = Registry::builtin();
+        assert!(registry.get("ahash").is_some());
+        assert!(registry.list().any(|a| a.name == "std"));
+        assert!(registry.get("nope").is_none());
+        assert_rejects("nope", 32, "prefix_a", "x");
+    }
+
+    #[test]
+    fn hashers() {
+        let registry = Registry::builtin();
+        let mut job = Job::new("ahash", 48);
+        job.params.insert("key0", "42");
+        assert!(registry.build(&job).is_ok());
+        let mut job = Job::new("siphash24", 40);
+        job.params.insert("key0", "0x0706050403020100");
+        assert!(registry.build(&job).is_ok());
+
+        assert_rejects("std", 65, "prefix_a", "x");
+        assert_rejects("std", 32, "key0", "1");
+        assert_rejects("ahash", 32, "key0", "not a number");
+        assert_rejects("siphash13", 32, "key1", "0xnope");
+    }
+
+    #[test]
+    fn digests() {
+        let registry = Registry::builtin();
+        for adapter in ["md5-ihv", "sha1", "sha256"] {
+            let mut job = Job::new(adapter, 80);
+            job.params.insert("prefix_b", "hello");
+            assert!(registry.build(&job).is_ok());
+        }
+        assert_rejects("md5-ihv", 72, "prefix_a", "x");
+        assert_rejects("md5-ihv", 64, "prefix_a_file", "/nonexistent/prefix");
+        assert_rejects("md5-ihv", 64, "trail_bits", "65");
+
+        let mut job = Job::new("sha1", 64);
+        job.trail_mask = Some(0xffff);
+        assert!(registry.build(&job).is_ok());
+        job.trail_mask = Some(0xff00);
+        assert!(registry.build(&job).is_err());
+        job.params.insert("trail_bits", "8");
+        assert!(registry.build(&job).is_err());
+        job.trail_mask = None;
+        assert!(registry.build(&job).is_ok());
+    }
+
+    #[test]
+    fn crc() {
+        let registry = Registry::builtin();
+        let mut job = Job::new("crc", 32);
+        job.params.insert("target", "0xcbf43926");
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("target", "cbf4392g");
+        let error = registry.build(&job).err().unwrap().to_string();
+        assert!(error.contains("parameter target"), "{}", error);
+    }
+
+    #[cfg(feature = "rustc")]
+    #[test]
+    fn rustc_def_path() {
+        let registry = Registry::builtin();
+        let mut job = Job::new("rustc-def-path", 48);
+        job.params.insert("parent", "mod inner");
+        job.params.insert("item_b", "struct Bar");
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("item_b", "bar_");
+        assert!(registry.build(&job).is_err());
+        job.params.insert("item_b", "struct Bar");
+        job.params.insert("profile", "1.64-nightly");
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("profile", "1.0");
+        assert!(registry.build(&job).is_err());
+        job.params.insert("profile", "1.72-dev");
+        job.params.insert("package_version", "0.1");
+        assert!(registry.build(&job).is_err());
+        job.params.insert("cargo_target_dir", "/nonexistent/target");
+        assert!(registry.build(&job).is_err());
+        assert_rejects("rustc-def-path", 48, "crate_type", "dylib");
+        assert_rejects("rustc-def-path", 48, "proof_dir", "target/proofs");
+
+        let mut job = Job::new("rustc-def-path", 64);
+        job.params.insert("proof_dir", "target/proofs");
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("item_b", "struct Bar");
+        assert!(registry.build(&job).is_err());
+        job.params.insert("item_b", "mod bar_");
+        job.params.insert("verify_proof", "yes");
+        assert!(registry.build(&job).is_err());
+        job.params.insert("verify_proof", "true");
+        job.params.insert("crate_type", "lib");
+        assert!(registry.build(&job).is_err());
+    }
+
+    #[cfg(feature = "rustc")]
+    #[test]
+    fn rustc_crate_params() {
+        use super::{crate_id_with_rustc, CONFLICTING_CRATE_PARAMS};
+        use crate::error::Result;
+        use crate::rustc::RUSTC_1_72;
+
+        for (first, second) in CONFLICTING_CRATE_PARAMS {
+            let mut job = Job::new("rustc-def-path", 48);
+            job.params.insert(first, "lib");
+            job.params.insert(second, "0.1.0");
+            let error = Registry::builtin().build(&job).err().unwrap();
+            assert!(
+                error.to_string().contains("mutually exclusive"),
+                "{}",
+                error
+            );
+        }
+
+        // `cargo build -v` of a new `playground` 0.1.0 with rustc 1.95.0
+        // passed `-C metadata=50441341099de93a` and named its incremental
+        // directory `playground-01fwhgb0n69sz`.
+        fn rustc_1_95() -> Result<String> {
+            Ok("rustc 1.95.0 (59807616e 2026-04-14)
+binary: rustc
+commit-hash: 59807616e1fa2540724bfbac14d7976d7e4a3860
+commit-date: 2026-04-14
+host: x86_64-unknown-linux-gnu
+release: 1.95.0
+LLVM version: 22.1.2
+"
+            .to_owned())
+        }
+        let mut job = Job::new("rustc-def-path", 48);
+        job.params.insert("package_version", "0.1.0");
+        let crate_id = crate_id_with_rustc(&job, &RUSTC_1_72, rustc_1_95).unwrap();
+        assert_eq!(crate_id.hash, 0x02a22ca8516c9e23);
+    }
+
+    #[cfg(feature = "rustc")]
+    #[test]
+    fn rustc_type_id() {
+        let registry = Registry::builtin();
+        let mut job = Job::new("rustc-typeid", 64);
+        job.params.insert("parent", "mod inner");
+        assert!(registry.build(&job).is_ok());
+        // Proofs need the whole TypeId in the point, 1.95 has 128 bits.
+        job.params.insert("proof_dir", "target/proofs");
+        assert!(registry.build(&job).is_err());
+        job.params.insert("profile", "1.64-nightly");
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("struct_b", "Foo");
+        assert!(registry.build(&job).is_err());
+        assert_rejects("rustc-typeid", 64, "profile", "1.72-dev");
+    }
+
+    #[cfg(feature = "rustc")]
+    #[test]
+    fn rustc_generic_type_id() {
+        let registry = Registry::builtin();
+        let mut job = Job::new("rustc-generic-type-id", 40);
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("parent", "mod inner");
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("proof_dir", "target/proofs");
+        assert!(registry.build(&job).is_err());
+        assert_rejects("rustc-generic-type-id", 40, "profile", "1.72-dev");
+        assert_rejects("rustc-generic-type-id", 40, "profile", "1.64-nightly");
+    }
+
+    #[cfg(feature = "rustc")]
+    #[test]
+    fn rustc_symbol() {
+        let registry = Registry::builtin();
+        let mut job = Job::new("rustc-symbol", 40);
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("crate_type", "lib");
+        assert!(registry.build(&job).is_ok());
+        assert_rejects("rustc-symbol", 40, "profile", "1.72-dev");
+        job.params.insert("parent", "impl");
+        assert!(registry.build(&job).is_err());
+    }
+
+    #[cfg(feature = "rustc")]
+    #[test]
+    fn rustc_crate_id() {
+        let registry = Registry::builtin();
+        let mut job = Job::new("rustc-crate-id", 48);
+        job.params.insert("crate_name_b", "other");
+        assert!(registry.build(&job).is_ok());
+        job.params.insert("crate_type_b", "dylib");
+        assert!(registry.build(&job).is_err());
+    }
+}
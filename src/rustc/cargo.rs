@@ -0,0 +1,408 @@
+//! Where the `-C metadata` of a crate comes from.
+//!
+//! cargo passes every crate a `-C metadata` hash of the package id,
+//! features, profile, target and compiler, which rustc mixes into the
+//! `StableCrateId`.  `CargoUnit` recomputes it from those inputs the way
+//! cargo 1.95 does, for units built without LTO.  Alternatively, a dev build
+//! leaves the crate id itself in the names of its incremental directories.
+
+use super::stable_hasher::StableHasher;
+use crate::error::{Error, Result};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::process::Command;
+
+/// Bumped by cargo when the metadata inputs change.
+const METADATA_VERSION: u8 = 2;
+
+/// Where a package comes from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CargoSource {
+    /// A path package, given relative to the workspace root, `""` for the
+    /// root package itself.  Path packages are taken to be workspace members.
+    Path(String),
+    CratesIo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CargoProfile {
+    Dev,
+    Release,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CargoTarget {
+    Lib,
+    Bin,
+}
+
+/// The hashes cargo computes for a unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CargoMetadata {
+    /// Passed as `-C metadata`.
+    pub c_metadata: u64,
+    /// Passed as `-C extra-filename` and part of the file names in `target/`.
+    pub unit_id: u64,
+}
+
+/// The inputs of one compiled crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CargoUnit {
+    pub package: String,
+    /// The semver version of the package, like `0.1.0`.
+    pub version: String,
+    pub source: CargoSource,
+    /// Enabled features, `default` included.
+    pub features: Vec<String>,
+    pub profile: CargoProfile,
+    pub target: CargoTarget,
+    /// The crate name, the package name with `-` replaced by `_` for libs.
+    pub target_name: String,
+    /// Output of `rustc -vV`.
+    pub rustc_verbose_version: String,
+    pub deps: Vec<CargoMetadata>,
+}
+
+impl CargoUnit {
+    /// The binary of the root package of a workspace, built with the dev profile.
+    pub fn new(package: &str, version: &str, rustc_verbose_version: &str) -> Self {
+        Self {
+            package: package.to_owned(),
+            version: version.to_owned(),
+            source: CargoSource::Path(String::new()),
+            features: Vec::new(),
+            profile: CargoProfile::Dev,
+            target: CargoTarget::Bin,
+            target_name: package.to_owned(),
+            rustc_verbose_version: rustc_verbose_version.to_owned(),
+            deps: Vec::new(),
+        }
+    }
+
+    /// Build the library target instead of the binary.
+    pub fn with_lib(mut self) -> Self {
+        self.target = CargoTarget::Lib;
+        self.target_name = self.package.replace('-', "_");
+        self
+    }
+
+    pub fn with_profile(mut self, profile: CargoProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn with_source(mut self, source: CargoSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_features(mut self, features: Vec<String>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Add the metadata of the direct dependencies.
+    pub fn with_deps(mut self, deps: &[CargoMetadata]) -> Self {
+        self.deps.extend_from_slice(deps);
+        self
+    }
+
+    pub fn metadata(&self) -> Result<CargoMetadata> {
+        let release = self.profile == CargoProfile::Release;
+        let mut hasher = StableHasher::new();
+        METADATA_VERSION.hash(&mut hasher);
+
+        // The package id.
+        self.package.hash(&mut hasher);
+        hash_version(&self.version, &mut hasher)?;
+        let is_member = match &self.source {
+            CargoSource::Path(path) => {
+                hasher.write_isize(1);
+                path.hash(&mut hasher);
+                true
+            }
+            CargoSource::CratesIo => {
+                hasher.write_isize(2);
+                "https://github.com/rust-lang/crates.io-index".hash(&mut hasher);
+                false
+            }
+        };
+
+        let mut features = self.features.clone();
+        features.sort();
+        features.dedup();
+        features.hash(&mut hasher);
+
+        // The profile: opt-level, `lto = false`, no codegen backend or
+        // units, debuginfo, no split debuginfo, debug assertions, overflow
+        // checks, no rpath, incremental, panic=unwind, strip, no rustflags
+        // and no trim-paths.
+        (if release { "3" } else { "0" }).hash(&mut hasher);
+        hasher.write_isize(1);
+        false.hash(&mut hasher);
+        hasher.write_isize(0);
+        hasher.write_isize(0);
+        hasher.write_isize(if release { 0 } else { 4 });
+        hasher.write_isize(0);
+        (!release).hash(&mut hasher);
+        (!release).hash(&mut hasher);
+        false.hash(&mut hasher);
+        (!release && is_member).hash(&mut hasher);
+        hasher.write_isize(0);
+        if release {
+            // Without debuginfo anywhere, cargo strips the one of std.
+            hasher.write_isize(1);
+            "debuginfo".hash(&mut hasher);
+        } else {
+            hasher.write_isize(0);
+        }
+        hasher.write_usize(0);
+        hasher.write_isize(0);
+
+        // `CompileMode::Build`, `Lto::OnlyObject` and the host kind.
+        hasher.write_isize(1);
+        hasher.write_isize(4);
+        0_u64.hash(&mut hasher);
+
+        self.target_name.hash(&mut hasher);
+        match self.target {
+            CargoTarget::Lib => {
+                // `TargetKind::Lib(vec![CrateType::Lib])`
+                hasher.write_isize(0);
+                hasher.write_usize(1);
+                hasher.write_isize(1);
+            }
+            CargoTarget::Bin => hasher.write_isize(1),
+        }
+        hash_rustc_version(&self.rustc_verbose_version, &mut hasher)?;
+        // Not a std crate.
+        false.hash(&mut hasher);
+
+        let mut c_metadata = hasher.clone();
+        let mut deps = self.deps.iter().map(|d| d.c_metadata).collect::<Vec<_>>();
+        deps.sort();
+        deps.hash(&mut c_metadata);
+
+        // No extra arguments and no rustflags.
+        let mut unit_id = hasher;
+        let mut deps = self.deps.iter().map(|d| d.unit_id).collect::<Vec<_>>();
+        deps.sort();
+        deps.hash(&mut unit_id);
+        unit_id.write_usize(0);
+        unit_id.write_usize(0);
+
+        Ok(CargoMetadata {
+            c_metadata: finish(c_metadata),
+            unit_id: finish(unit_id),
+        })
+    }
+}
+
+/// `Hasher::finish` of cargo's hasher.
+fn finish(hasher: StableHasher) -> u64 {
+    let (a, b) = hasher.finalize();
+    a.wrapping_mul(3).wrapping_add(b)
+}
+
+/// Hash a `semver::Version`.
+fn hash_version(version: &str, hasher: &mut StableHasher) -> Result<()> {
+    let invalid = || Error::Invalid(format!("invalid package version {:?}", version));
+    let (version, build) = version.split_once('+').unwrap_or((version, ""));
+    let (version, pre) = version.split_once('-').unwrap_or((version, ""));
+    let numbers = version
+        .split('.')
+        .map(|n| n.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>>>()?;
+    let [major, minor, patch] = numbers[..] else {
+        return Err(invalid());
+    };
+    (major, minor, patch, pre, build).hash(hasher);
+    Ok(())
+}
+
+/// Hash the compiler version: all of `rustc -vV` for stable releases, only
+/// the channel and host for nightly, beta and dev builds.
+fn hash_rustc_version(verbose: &str, hasher: &mut StableHasher) -> Result<()> {
+    let field = |name: &str| {
+        verbose
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+            .ok_or_else(|| Error::Invalid(format!("no {} in the rustc version", name)))
+    };
+    let release = field("release")?;
+    match release.split_once('-') {
+        None => verbose.lines().for_each(|line| line.hash(hasher)),
+        Some((_, pre)) => {
+            pre.split('.').next().hash(hasher);
+            field("host")?.hash(hasher);
+        }
+    }
+    Ok(())
+}
+
+/// `rustc -vV` of the compiler cargo would use, `$RUSTC` or `rustc`.
+pub fn local_rustc_verbose_version() -> Result<String> {
+    let output = local_rustc().arg("-vV").output()?;
+    if !output.status.success() {
+        return Err(Error::Invalid(format!(
+            "rustc -vV failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| Error::Invalid("rustc -vV printed invalid UTF-8".to_owned()))
+}
+
+/// The version of the compiler cargo would use, like
+/// `1.95.0 (59807616e 2026-04-14)`: the string it hashes into crate ids.
+pub fn local_rustc_version() -> Result<String> {
+    rustc_version(&local_rustc_verbose_version()?)
+}
+
+/// The version in the output of `rustc -vV`, like `local_rustc_version`.
+pub fn rustc_version(verbose: &str) -> Result<String> {
+    verbose
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("rustc "))
+        .map(str::to_owned)
+        .ok_or_else(|| Error::Invalid("rustc -vV printed no version".to_owned()))
+}
+
+/// A command running `$RUSTC`, or `rustc` when it is not set.
+pub(super) fn local_rustc() -> Command {
+    Command::new(std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()))
+}
+
+/// The `-C metadata` values passed for `crate_name` in the output of
+/// `cargo build -v`.
+pub fn metadata_from_build_output(output: &str, crate_name: &str) -> Vec<String> {
+    let mut found = output
+        .lines()
+        .filter(|line| {
+            line.split_whitespace()
+                .collect::<Vec<_>>()
+                .windows(2)
+                .any(|w| w == ["--crate-name", crate_name])
+        })
+        .filter_map(|line| {
+            let (_, rest) = line.split_once("metadata=")?;
+            Some(
+                rest.split(|c: char| !c.is_ascii_hexdigit())
+                    .next()?
+                    .to_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// The crate ids of `crate_name` from the incremental directories of dev
+/// builds in `target_dir`, named like `playground-01fwhgb0n69sz` with the
+/// crate id in base 36.
+pub fn crate_ids_in_target(target_dir: &Path, crate_name: &str) -> Result<Vec<u64>> {
+    let prefix = format!("{}-", crate_name);
+    let mut found = Vec::new();
+    for profile in std::fs::read_dir(target_dir)? {
+        let Ok(sessions) = std::fs::read_dir(profile?.path().join("incremental")) else {
+            continue;
+        };
+        for session in sessions {
+            let name = session?.file_name();
+            let id = name
+                .to_str()
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(|id| u64::from_str_radix(id, 36).ok());
+            found.extend(id);
+        }
+    }
+    found.sort();
+    found.dedup();
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        crate_ids_in_target, metadata_from_build_output, CargoMetadata, CargoProfile, CargoSource,
+        CargoUnit,
+    };
+
+    const RUSTC_1_95: &str = "rustc 1.95.0 (59807616e 2026-04-14)
+binary: rustc
+commit-hash: 59807616e1fa2540724bfbac14d7976d7e4a3860
+commit-date: 2026-04-14
+host: x86_64-unknown-linux-gnu
+release: 1.95.0
+LLVM version: 22.1.2
+";
+
+    // All checked against `cargo build -v` of cargo 1.95.
+    #[test]
+    fn cargo_1_95() {
+        let bin = CargoUnit::new("playground", "0.1.0", RUSTC_1_95);
+        let expected = CargoMetadata {
+            c_metadata: 0x50441341099de93a,
+            unit_id: 0xf3044befbaff2b40,
+        };
+        assert_eq!(bin.metadata().unwrap(), expected);
+        let expected = CargoMetadata {
+            c_metadata: 0xaacd3658dc54e955,
+            unit_id: 0x1c727054654e38ff,
+        };
+        let release = bin.with_profile(CargoProfile::Release);
+        assert_eq!(release.metadata().unwrap(), expected);
+
+        let cfg_if = CargoUnit::new("cfg-if", "1.0.5", RUSTC_1_95)
+            .with_lib()
+            .with_source(CargoSource::CratesIo)
+            .metadata()
+            .unwrap();
+        assert_eq!(cfg_if.c_metadata, 0xb202358ff9987f40);
+        assert_eq!(cfg_if.unit_id, 0xd995ec1fb643b77d);
+        let lib = CargoUnit::new("withdep", "0.1.0", RUSTC_1_95)
+            .with_lib()
+            .with_deps(&[cfg_if]);
+        assert_eq!(lib.metadata().unwrap().c_metadata, 0x82c048c1dd08258a);
+
+        let nightly = RUSTC_1_95.replace("release: 1.95.0", "release: 1.96.0-nightly");
+        let a = CargoUnit::new("playground", "0.1.0", &nightly);
+        let b = CargoUnit::new("playground", "0.1.0", &nightly.replace("22.1.2", "23"));
+        assert_eq!(a.metadata().unwrap(), b.metadata().unwrap());
+        assert!(CargoUnit::new("playground", "0.1", RUSTC_1_95)
+            .metadata()
+            .is_err());
+    }
+
+    #[test]
+    fn build_output() {
+        let output = "   Compiling playground v0.1.0 (/tmp/playground)
+     Running `rustc --crate-name playground --edition=2021 src/main.rs --crate-type bin -C metadata=50441341099de93a -C extra-filename=-f3044befbaff2b40`
+     Running `rustc --crate-name cfg_if --edition=2018 src/lib.rs -C metadata=b202358ff9987f40`";
+        assert_eq!(
+            metadata_from_build_output(output, "playground"),
+            ["50441341099de93a"]
+        );
+        assert!(metadata_from_build_output(output, "cfg").is_empty());
+    }
+
+    #[test]
+    fn incremental_dirs() {
+        let dir = std::env::temp_dir().join(format!("crate-ids-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("debug/incremental/playground-01fwhgb0n69sz")).unwrap();
+        std::fs::create_dir_all(dir.join("debug/incremental/other-3kmveynzccm1q")).unwrap();
+        let found = crate_ids_in_target(&dir, "playground");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(found.unwrap(), [0x02a22ca8516c9e23]);
+
+        // The crate id of the binary in `cargo_1_95`.
+        let crate_id = crate::rustc::RUSTC_1_95.crate_id(
+            "playground",
+            true,
+            vec!["50441341099de93a".to_owned()],
+        );
+        assert_eq!(crate_id.hash, 0x02a22ca8516c9e23);
+    }
+}
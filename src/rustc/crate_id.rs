@@ -0,0 +1,174 @@
+//! Collisions of `StableCrateId`s.
+//!
+//! Every `DefPathHash` and `TypeId` of a crate starts from its crate id, and
+//! two crates with the same id cannot be linked together.  The `-C metadata`
+//! values are hashed as strings, so the free bits live in one of them.
+
+use super::def_path::StableCrateId;
+use super::profile::Profile;
+use crate::adapters::{low_mask, truncated_point_methods, TruncatedPoint};
+use crate::{HashAdapter, Reporter};
+use core::ops::ControlFlow;
+
+/// One side of a `CrateIdAdapter`: the crate whose `-C metadata` value is a
+/// prefix followed by the point in hex.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrateConfig {
+    pub crate_name: String,
+    pub is_exe: bool,
+    pub metadata_prefix: String,
+}
+
+impl CrateConfig {
+    pub fn new(crate_name: &str, is_exe: bool, metadata_prefix: &str) -> Self {
+        Self {
+            crate_name: crate_name.to_owned(),
+            is_exe,
+            metadata_prefix: metadata_prefix.to_owned(),
+        }
+    }
+}
+
+/// Collides the crate ids of two crates built by the compiler of `profile`,
+/// each passed a single `-C metadata` value named by its prefix and the
+/// point in hex.
+///
+/// Only the lowest `bits` bits of the crate id are compared.
+pub struct CrateIdAdapter {
+    profile: &'static Profile,
+    rustc_version: String,
+    crates: [CrateConfig; 2],
+    point: TruncatedPoint,
+    reporter: Reporter,
+}
+
+impl CrateIdAdapter {
+    pub fn new(profile: &'static Profile, crate_a: CrateConfig, crate_b: CrateConfig) -> Self {
+        Self {
+            profile,
+            rustc_version: profile.rustc_version.to_owned(),
+            crates: [crate_a, crate_b],
+            point: TruncatedPoint::new(64, low_mask(18)),
+            reporter: Reporter::default(),
+        }
+    }
+
+    /// Hash `version` instead of the version string of the profile.
+    pub fn with_rustc_version(mut self, version: &str) -> Self {
+        self.rustc_version = version.to_owned();
+        self
+    }
+
+    /// Compare only the lowest `bits` bits, with points distinguished by
+    /// `trail_mask`.
+    pub fn with_bits(mut self, bits: u32, trail_mask: u64) -> Self {
+        self.point = TruncatedPoint::new(bits, trail_mask);
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// The `-C metadata` value for point `x`, of the first crate when `bi`
+    /// is set.
+    pub fn metadata(&self, x: u64, bi: bool) -> String {
+        self.point
+            .hex_name(&self.crates[!bi as usize].metadata_prefix, x)
+    }
+
+    /// The crate id for point `x`.
+    pub fn crate_id(&self, x: u64, bi: bool) -> StableCrateId {
+        let config = &self.crates[!bi as usize];
+        StableCrateId::new(
+            &config.crate_name,
+            config.is_exe,
+            vec![self.metadata(x, bi)],
+            &self.rustc_version,
+            self.profile,
+        )
+    }
+
+    /// The `rustc` arguments building the crate for point `x`.
+    pub fn rustc_args(&self, x: u64, bi: bool) -> String {
+        let config = &self.crates[!bi as usize];
+        format!(
+            "--crate-name {} --crate-type {} -C metadata={}",
+            config.crate_name,
+            if config.is_exe { "bin" } else { "lib" },
+            self.metadata(x, bi)
+        )
+    }
+}
+
+impl HashAdapter for CrateIdAdapter {
+    type Point = u64;
+
+    truncated_point_methods!();
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.point.truncate(self.crate_id(x, bi).hash)
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        let hash = self.crate_id(a, true).hash;
+        assert_eq!(
+            self.point.truncate(hash),
+            self.point.truncate(self.crate_id(b, false).hash)
+        );
+        self.reporter.report(
+            &a,
+            &b,
+            format_args!(
+                "found collision! (a, {}) (b, {}) crate_id={:016x}",
+                self.rustc_args(a, true),
+                self.rustc_args(b, false),
+                hash
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrateConfig, CrateIdAdapter};
+    use crate::rustc::{RUSTC_1_72, RUSTC_1_95};
+    use crate::HashAdapter;
+
+    #[test]
+    fn metadata_values() {
+        let adapter = CrateIdAdapter::new(
+            &RUSTC_1_72,
+            CrateConfig::new("playground", true, ""),
+            CrateConfig::new("other", false, "x"),
+        );
+        // The crate of `profile::tests::rustc_1_72`.
+        assert_eq!(
+            adapter.metadata(0x051dac071847dbb3, true),
+            "051dac071847dbb3"
+        );
+        assert_eq!(
+            adapter.next_point(0x051dac071847dbb3, true),
+            0xc271afeb41224329
+        );
+        assert_eq!(
+            adapter.rustc_args(0xc0ffee, false),
+            "--crate-name other --crate-type lib -C metadata=x0000000000c0ffee"
+        );
+
+        // The library of `profile::tests::rustc_1_95`, with the version
+        // string of a custom build.
+        let adapter = CrateIdAdapter::new(
+            &RUSTC_1_72,
+            CrateConfig::new("symtest", false, ""),
+            CrateConfig::new("symtest", false, ""),
+        )
+        .with_rustc_version(RUSTC_1_95.rustc_version);
+        assert_eq!(
+            adapter.next_point(0x051dac071847dbb3, false),
+            0xdb7ac9fa2f31ed54
+        );
+    }
+}
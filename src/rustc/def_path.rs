@@ -0,0 +1,588 @@
+//! rustc's item identities: `StableCrateId`s and `DefPathHash`es.
+//!
+//! Every item is named by the path of `DefPathData` segments leading to it
+//! from the crate root.  Its `DefPathHash` is the crate id and a 64-bit hash
+//! chained over the segments: each segment hashes the parent's hash, the
+//! discriminant of its data, its name if any, and a disambiguator telling
+//! apart items with the same name.  `TypeId`s and symbol names are built on
+//! top of it, so colliding the hash of a module collides everything in it.
+
+use super::profile::{DefPathLayout, Profile};
+use super::proof::{ProofCrate, Proofs};
+use super::stable_hasher::{HashStable, StableHasher};
+use crate::adapters::{low_mask, truncated_point_methods, TruncatedPoint};
+use crate::error::{Error, Result};
+use crate::{HashAdapter, Reporter};
+use core::fmt;
+use core::ops::ControlFlow;
+use std::hash::Hasher;
+use std::path::PathBuf;
+
+/// The hash identifying a crate, from its name, `-C metadata` values, crate
+/// type and the rustc version.
+///
+/// It also remembers the profile of the compiler, which all paths in the
+/// crate are hashed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StableCrateId {
+    pub hash: u64,
+    pub profile: &'static Profile,
+}
+
+impl StableCrateId {
+    /// The crate id computed by the compiler of `profile`, built as
+    /// `version`, which may differ from the profile's version string.
+    pub fn new(
+        crate_name: &str,
+        is_exe: bool,
+        mut metadata: Vec<String>,
+        version: &str,
+        profile: &'static Profile,
+    ) -> Self {
+        let mut hasher = StableHasher::new_with_rounds(profile.rounds);
+        hasher.write_str(crate_name);
+        // Like rustc, the order and repetitions of `-C metadata` do not matter.
+        metadata.sort();
+        metadata.dedup();
+        hasher.write(b"metadata");
+        for s in &metadata {
+            hasher.write_usize(s.len());
+            hasher.write(s.as_bytes());
+        }
+        hasher.write(if is_exe { b"exe" } else { b"lib" });
+        hasher.write(version.as_bytes());
+        Self {
+            hash: hasher.finish(),
+            profile,
+        }
+    }
+
+    /// A `StableHasher` of the compiler this crate id is from.
+    pub fn hasher(&self) -> StableHasher {
+        StableHasher::new_with_rounds(self.profile.rounds)
+    }
+}
+
+/// The data of one path segment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DefPathData {
+    CrateRoot,
+    Impl,
+    ForeignMod,
+    Use,
+    GlobalAsm,
+    /// Modules, types, traits and enum variants.
+    TypeNs(String),
+    /// Functions, constants, statics and fields.
+    ValueNs(String),
+    MacroNs(String),
+    LifetimeNs(String),
+    ClosureExpr,
+    Ctor,
+    AnonConst,
+    ImplTrait,
+}
+
+impl DefPathData {
+    /// The discriminant rustc hashes for this kind of segment.
+    pub fn discriminant(&self) -> isize {
+        match self {
+            Self::CrateRoot => 0,
+            Self::Impl => 1,
+            Self::ForeignMod => 2,
+            Self::Use => 3,
+            Self::GlobalAsm => 4,
+            Self::TypeNs(_) => 5,
+            Self::ValueNs(_) => 6,
+            Self::MacroNs(_) => 7,
+            Self::LifetimeNs(_) => 8,
+            Self::ClosureExpr => 9,
+            Self::Ctor => 10,
+            Self::AnonConst => 11,
+            Self::ImplTrait => 12,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::TypeNs(name)
+            | Self::ValueNs(name)
+            | Self::MacroNs(name)
+            | Self::LifetimeNs(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DefPathData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => {
+                let kind = match self {
+                    Self::CrateRoot => "crate",
+                    Self::Impl => "impl",
+                    Self::ForeignMod => "foreign_mod",
+                    Self::Use => "use",
+                    Self::GlobalAsm => "global_asm",
+                    Self::ClosureExpr => "closure",
+                    Self::Ctor => "constructor",
+                    Self::AnonConst => "constant",
+                    _ => "opaque",
+                };
+                write!(f, "{{{}}}", kind)
+            }
+        }
+    }
+}
+
+/// A path segment with its disambiguator, which counts the previous
+/// siblings with the same data and is 0 for most items.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DisambiguatedDefPathData {
+    pub data: DefPathData,
+    pub disambiguator: u32,
+}
+
+impl fmt::Display for DisambiguatedDefPathData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.disambiguator {
+            0 => write!(f, "{}", self.data),
+            n => write!(f, "{}#{}", self.data, n),
+        }
+    }
+}
+
+/// The identity of an item across compilation sessions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DefPathHash {
+    pub crate_id: StableCrateId,
+    pub local: u64,
+}
+
+impl DefPathHash {
+    /// The hash of the crate root.
+    pub fn crate_root(crate_id: StableCrateId) -> Self {
+        match crate_id.profile.def_path_layout {
+            DefPathLayout::Rustc1_64 => {
+                Self { crate_id, local: 0 }.child(&DefPathData::CrateRoot, 0)
+            }
+            DefPathLayout::Rustc1_95 => Self {
+                crate_id,
+                local: crate_id.hash,
+            },
+        }
+    }
+
+    /// The hash of the item `data` inside this one.
+    pub fn child(&self, data: &DefPathData, disambiguator: u32) -> Self {
+        let mut hasher = self.crate_id.hasher();
+        match self.crate_id.profile.def_path_layout {
+            DefPathLayout::Rustc1_64 => self.hash_stable(&mut (), &mut hasher),
+            DefPathLayout::Rustc1_95 => hasher.write_u64(self.local),
+        }
+        hasher.write_isize(data.discriminant());
+        if let Some(name) = data.name() {
+            hasher.write_str(name);
+        }
+        hasher.write_u32(disambiguator);
+        Self {
+            crate_id: self.crate_id,
+            local: hasher.finish(),
+        }
+    }
+}
+
+impl<CTX> HashStable<CTX> for DefPathHash {
+    fn hash_stable(&self, hcx: &mut CTX, hasher: &mut StableHasher) {
+        (self.crate_id.hash, self.local).hash_stable(hcx, hasher);
+    }
+}
+
+/// Kinds of items, and the path segment they get for a name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    Mod,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    TypeAlias,
+    Fn,
+    Const,
+    Static,
+}
+
+impl ItemKind {
+    pub const ALL: [ItemKind; 9] = [
+        Self::Mod,
+        Self::Struct,
+        Self::Enum,
+        Self::Union,
+        Self::Trait,
+        Self::TypeAlias,
+        Self::Fn,
+        Self::Const,
+        Self::Static,
+    ];
+
+    /// The keyword declaring the item.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Mod => "mod",
+            Self::Struct => "struct",
+            Self::Enum => "enum",
+            Self::Union => "union",
+            Self::Trait => "trait",
+            Self::TypeAlias => "type",
+            Self::Fn => "fn",
+            Self::Const => "const",
+            Self::Static => "static",
+        }
+    }
+
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.keyword() == keyword)
+    }
+
+    pub fn data(self, name: &str) -> DefPathData {
+        match self {
+            Self::Fn | Self::Const | Self::Static => DefPathData::ValueNs(name.to_owned()),
+            _ => DefPathData::TypeNs(name.to_owned()),
+        }
+    }
+
+    /// A minimal declaration of the item, `body` is put inside modules.
+    pub fn declaration(self, name: &str, body: &str) -> String {
+        match self {
+            Self::Mod if body.is_empty() => format!("mod {} {{}}", name),
+            Self::Mod => format!("mod {} {{ {} }}", name, body),
+            Self::Struct => format!("struct {};", name),
+            Self::Enum => format!("enum {} {{}}", name),
+            Self::Union => format!("union {} {{ x: u8 }}", name),
+            Self::Trait => format!("trait {} {{}}", name),
+            Self::TypeAlias => format!("type {} = ();", name),
+            Self::Fn => format!("fn {}() {{}}", name),
+            Self::Const => format!("const {}: () = ();", name),
+            Self::Static => format!("static {}: () = ();", name),
+        }
+    }
+}
+
+/// A path from the crate root, like `fn main::mod foo_0123`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DefPath {
+    pub crate_id: StableCrateId,
+    pub data: Vec<DisambiguatedDefPathData>,
+}
+
+impl DefPath {
+    /// The path of the crate root.
+    pub fn new(crate_id: StableCrateId) -> Self {
+        Self {
+            crate_id,
+            data: Vec::new(),
+        }
+    }
+
+    /// Append the segment `data`.
+    pub fn push(&mut self, data: DefPathData, disambiguator: u32) {
+        self.data.push(DisambiguatedDefPathData {
+            data,
+            disambiguator,
+        });
+    }
+
+    /// This path with the segment `data` appended.
+    pub fn child(&self, data: DefPathData, disambiguator: u32) -> Self {
+        let mut path = self.clone();
+        path.push(data, disambiguator);
+        path
+    }
+
+    /// Parse `::`-separated segments of the form `keyword name` with an
+    /// optional `#disambiguator`, where the keyword is one of
+    /// `ItemKind::keyword`, `field` or `variant`, or a bare `impl`.
+    pub fn parse(crate_id: StableCrateId, s: &str) -> Result<Self> {
+        let mut path = Self::new(crate_id);
+        for segment in s.split("::").map(str::trim).filter(|s| !s.is_empty()) {
+            let (segment, disambiguator) = match segment.split_once('#') {
+                Some((segment, n)) => {
+                    let n = n.parse().map_err(|e| {
+                        Error::Invalid(format!("invalid disambiguator in {:?}: {}", s, e))
+                    })?;
+                    (segment.trim(), n)
+                }
+                None => (segment, 0),
+            };
+            let data = match segment.split_once(' ') {
+                None if segment == "impl" => DefPathData::Impl,
+                Some(("field", name)) => DefPathData::ValueNs(name.trim().to_owned()),
+                Some(("variant", name)) => DefPathData::TypeNs(name.trim().to_owned()),
+                Some((keyword, name)) => ItemKind::from_keyword(keyword)
+                    .ok_or_else(|| {
+                        Error::Invalid(format!("unknown item kind {:?} in {:?}", keyword, s))
+                    })?
+                    .data(name.trim()),
+                None => {
+                    return Err(Error::Invalid(format!(
+                        "path segment {:?} needs an item kind, like \"mod {}\"",
+                        segment, segment
+                    )))
+                }
+            };
+            path.push(data, disambiguator);
+        }
+        Ok(path)
+    }
+
+    pub fn hash(&self) -> DefPathHash {
+        self.data
+            .iter()
+            .fold(DefPathHash::crate_root(self.crate_id), |parent, segment| {
+                parent.child(&segment.data, segment.disambiguator)
+            })
+    }
+}
+
+impl fmt::Display for DefPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("crate")?;
+        for segment in &self.data {
+            write!(f, "::{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collides the `DefPathHash`es of two items under the same parent, named by
+/// a prefix and the point in hex, like `mod foo_00c0ffee` and `mod bar_1234`.
+///
+/// Only the lowest `bits` bits of the local hash are compared.  With all 64
+/// bits, the two items and everything inside them are the same to rustc.
+pub struct DefPathAdapter {
+    parent: DefPathHash,
+    items: [(ItemKind, String); 2],
+    point: TruncatedPoint,
+    reporter: Reporter,
+    proofs: Proofs,
+}
+
+impl DefPathAdapter {
+    pub fn new(parent: &DefPath, item_a: (ItemKind, &str), item_b: (ItemKind, &str)) -> Self {
+        Self {
+            parent: parent.hash(),
+            items: [
+                (item_a.0, item_a.1.to_owned()),
+                (item_b.0, item_b.1.to_owned()),
+            ],
+            point: TruncatedPoint::new(64, low_mask(18)),
+            reporter: Reporter::default(),
+            proofs: Proofs::default(),
+        }
+    }
+
+    /// Compare only the lowest `bits` bits, with points distinguished by
+    /// `trail_mask`.
+    pub fn with_bits(mut self, bits: u32, trail_mask: u64) -> Self {
+        self.point = TruncatedPoint::new(bits, trail_mask);
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Write a `ProofCrate` for every collision of all 64 bits into a
+    /// directory of `dir`, named by the crate and the hash.  `proof` gives
+    /// the crate name and metadata, and `parent` the path from the root of
+    /// the proof's snippet to the items.
+    ///
+    /// Two modules with the same hash hold structs with the same `TypeId`,
+    /// so both items and all segments of `parent` must be modules.
+    pub fn with_proof_dir<P: Into<PathBuf>>(
+        mut self,
+        dir: P,
+        proof: ProofCrate,
+        parent: &[DisambiguatedDefPathData],
+    ) -> Result<Self> {
+        if self.items.iter().any(|(kind, _)| *kind != ItemKind::Mod) {
+            return Err(Error::Invalid(
+                "proofs need the colliding items to be modules".to_owned(),
+            ));
+        }
+        self.proofs.set_dir(dir.into(), proof, parent)?;
+        Ok(self)
+    }
+
+    /// The name of the item for point `x`, the first item when `bi` is set.
+    pub fn name(&self, x: u64, bi: bool) -> String {
+        let (_, prefix) = &self.items[!bi as usize];
+        self.point.hex_name(prefix, x)
+    }
+
+    pub fn kind(&self, bi: bool) -> ItemKind {
+        self.items[!bi as usize].0
+    }
+
+    /// Build and run every proof with the local compiler, and add the
+    /// outcome to the report.
+    pub fn with_proof_verification(mut self) -> Self {
+        self.proofs.set_verify();
+        self
+    }
+
+    /// The proof that the modules for `a` and `b` collide.
+    pub fn proof(&self, a: u64, b: u64) -> Option<ProofCrate> {
+        let proof = self.proofs.template()?;
+        let (name_a, name_b) = (self.name(a, true), self.name(b, false));
+        let items = format!(
+            "pub mod {} {{\n    pub struct S;\n}}\npub mod {} {{\n    pub struct S;\n}}",
+            name_a, name_b
+        );
+        let prefix = self.proofs.prefix();
+        let note = format!(
+            "`{}` and `{}` have the same DefPathHash {:016x}, so the\nstructs inside them have the same TypeId.\n\nBuild with ./build.sh, it passes -C metadata={}.",
+            name_a,
+            name_b,
+            self.def_path_hash(a, true).local,
+            proof.metadata.join(",")
+        );
+        Some(proof.clone().with_items(
+            &self.proofs.in_modules(&items),
+            &format!("{}{}::S", prefix, name_a),
+            &format!("{}{}::S", prefix, name_b),
+            &note,
+        ))
+    }
+
+    /// The full `DefPathHash` of the item for point `x`.
+    pub fn def_path_hash(&self, x: u64, bi: bool) -> DefPathHash {
+        let data = self.kind(bi).data(&self.name(x, bi));
+        self.parent.child(&data, 0)
+    }
+}
+
+impl HashAdapter for DefPathAdapter {
+    type Point = u64;
+
+    truncated_point_methods!();
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.point.truncate(self.def_path_hash(x, bi).local)
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        let hash = self.def_path_hash(a, true);
+        let other = self.def_path_hash(b, false);
+        assert_eq!(
+            self.point.truncate(hash.local),
+            self.point.truncate(other.local)
+        );
+
+        let files = match hash == other {
+            true => self
+                .proofs
+                .write_for_report(hash.local, self.parent.crate_id, || self.proof(a, b)),
+            false => String::new(),
+        };
+        self.reporter.report(
+            &a,
+            &b,
+            format_args!(
+                "found collision! (a, {}) (b, {}) def_path_hash={:016x}{}",
+                self.kind(true).declaration(&self.name(a, true), ""),
+                self.kind(false).declaration(&self.name(b, false), ""),
+                hash.local,
+                files
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DefPath, DefPathAdapter, DefPathData, ItemKind, StableCrateId};
+    use crate::rustc::profile::RUSTC_1_72;
+    use crate::rustc::proof::ProofCrate;
+    use crate::HashAdapter;
+
+    fn playground() -> StableCrateId {
+        RUSTC_1_72.crate_id("playground", true, vec!["051dac071847dbb3".to_owned()])
+    }
+
+    #[test]
+    fn playground_hashes() {
+        // The crate and root hashes are the ones `make_mod_id` of the
+        // baseline `examples/type_id_128.rs` computed, the rest only come
+        // from this model.
+        let crate_id = playground();
+        assert_eq!(crate_id.hash, 0xc271afeb41224329);
+        let path = DefPath::new(crate_id);
+        assert_eq!(path.hash().local, 0x176f1518d8791cb8);
+        let path = path.child(DefPathData::ValueNs("main".to_owned()), 0);
+        assert_eq!(path.hash().local, 0xbadf855b99d7423f);
+        let path = path.child(DefPathData::TypeNs("foo_0000000000c0ffee".to_owned()), 0);
+        assert_eq!(path.hash().local, 0x544d6897cf2dcea3);
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let path = DefPath::parse(playground(), "fn main :: mod foo#1::impl::field x").unwrap();
+        assert_eq!(path.to_string(), "crate::main::foo#1::{impl}::x");
+        assert_eq!(path.data[1].data, DefPathData::TypeNs("foo".to_owned()));
+        assert_eq!(path.data[3].data, DefPathData::ValueNs("x".to_owned()));
+        assert_eq!(
+            path.hash(),
+            DefPath::parse(playground(), "")
+                .unwrap()
+                .hash()
+                .child(&DefPathData::ValueNs("main".to_owned()), 0)
+                .child(&DefPathData::TypeNs("foo".to_owned()), 1)
+                .child(&DefPathData::Impl, 0)
+                .child(&DefPathData::ValueNs("x".to_owned()), 0)
+        );
+        assert!(DefPath::parse(playground(), "main").is_err());
+        assert!(DefPath::parse(playground(), "fun main").is_err());
+        assert!(DefPath::parse(playground(), "fn main#x").is_err());
+    }
+
+    #[test]
+    fn adapter_names() {
+        let parent = DefPath::parse(playground(), "fn main").unwrap();
+        let adapter = DefPathAdapter::new(&parent, (ItemKind::Mod, "foo_"), (ItemKind::Fn, "bar_"))
+            .with_bits(40, 0xff);
+        assert_eq!(adapter.name(0xc0ffee, true), "foo_0000c0ffee");
+        let hash = parent
+            .child(DefPathData::ValueNs("bar_0000c0ffee".to_owned()), 0)
+            .hash();
+        assert_eq!(adapter.def_path_hash(0xc0ffee, false), hash);
+        assert_eq!(
+            adapter.next_point(0xc0ffee, false),
+            hash.local & 0xff_ffff_ffff
+        );
+    }
+
+    #[test]
+    fn adapter_proof() {
+        let inner = DefPath::parse(playground(), "mod inner").unwrap();
+        let proof = ProofCrate::new("playground", vec!["051dac071847dbb3".to_owned()], false);
+        let adapter = DefPathAdapter::new(&inner, (ItemKind::Mod, "foo_"), (ItemKind::Mod, "bar_"))
+            .with_proof_dir("proofs", proof.clone(), &inner.data)
+            .unwrap();
+        let proof = adapter.proof(1, 2).unwrap();
+        assert_eq!(
+            proof.items,
+            "pub mod inner {\n    pub mod foo_0000000000000001 {\n        pub struct S;\n    }\n    \
+             pub mod bar_0000000000000002 {\n        pub struct S;\n    }\n}"
+        );
+        assert_eq!(proof.types[1], "inner::bar_0000000000000002::S");
+
+        let adapter = DefPathAdapter::new(&inner, (ItemKind::Mod, "foo_"), (ItemKind::Fn, "bar_"));
+        assert!(adapter
+            .with_proof_dir("proofs", ProofCrate::new("playground", vec![], false), &[])
+            .is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! The hashing code of rustc's `rustc_data_structures`, ported to stable
+//! Rust: `SipHasher128`, `StableHasher` and the `HashStable` impls behind
+//! `TypeId`s, `DefPathHash`es and symbol hashes.
+//!
+//! Enabled by the `rustc` feature.
+
+mod cargo;
+mod crate_id;
+mod def_path;
+mod hashes;
+mod profile;
+mod proof;
+// The ported files keep rustc's names, `_0` and `_1` included.
+#[allow(clippy::just_underscores_and_digits)]
+mod sip128;
+#[allow(clippy::just_underscores_and_digits)]
+mod stable_hasher;
+mod symbol;
+mod type_id;
+
+pub use self::cargo::{
+    crate_ids_in_target, local_rustc_verbose_version, local_rustc_version,
+    metadata_from_build_output, rustc_version, CargoMetadata, CargoProfile, CargoSource,
+    CargoTarget, CargoUnit,
+};
+pub use self::crate_id::{CrateConfig, CrateIdAdapter};
+pub use self::def_path::{
+    DefPath, DefPathAdapter, DefPathData, DefPathHash, DisambiguatedDefPathData, ItemKind,
+    StableCrateId,
+};
+pub use self::hashes::{Hash128, Hash64};
+pub use self::profile::{
+    AdtLayout, DefPathLayout, Profile, SymbolLayout, PROFILES, RUSTC_1_64, RUSTC_1_72, RUSTC_1_95,
+};
+pub use self::proof::{ProofCrate, Verification};
+pub use self::sip128::{SipHasher128, SipRounds};
+pub use self::stable_hasher::{
+    HashStable, HashingControls, StableHasher, StableHasherResult, StableOrd, ToStableHashKey,
+};
+pub use self::symbol::SymbolHashAdapter;
+pub use self::type_id::{GenericTypeIdAdapter, StructTypeIdAdapter};
+
+/// The stable hash of `val`.
+pub fn hash_of<T: HashStable<CTX>, CTX>(hcx: &mut CTX, val: T) -> (u64, u64) {
+    let mut hasher = StableHasher::new();
+    val.hash_stable(hcx, &mut hasher);
+    hasher.finalize()
+}
@@ -0,0 +1,428 @@
+//! What changes between compiler versions.
+//!
+//! The hashes are only useful against the compiler that will see the
+//! colliding items: the SipHash variant, the version string hashed into
+//! crate ids, whether snippets end up inside `fn main`, how paths are
+//! hashed, and how `TypeId`s and symbols hash types all depend on it.  A
+//! `Profile` names one such compiler.
+
+use super::def_path::{DefPath, DefPathData, DefPathHash, StableCrateId};
+use super::sip128::SipRounds;
+use super::stable_hasher::{HashStable, HashingControls};
+use std::hash::Hasher;
+
+/// The layout of the `AdtDef` hash behind a struct's `TypeId`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AdtLayout {
+    /// `AdtDefData` of rustc 1.64: variants with ctor, discr and fields,
+    /// `AdtFlags` and `ReprOptions` with the field shuffle seed.
+    ///
+    /// Only structs with a single field and no generic arguments are
+    /// modelled, the shape of the baseline `examples/type_id.rs`.
+    Rustc1_64,
+    /// `AdtDefData` of rustc 1.95: fields also hash their safety and
+    /// default value, variants their tainted state, and `ReprOptions` one
+    /// byte less.
+    Rustc1_95,
+}
+
+/// How a `DefPathHash` is derived from the one of its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DefPathLayout {
+    /// The whole parent hash is hashed, and the crate root is hashed like
+    /// any other segment.
+    Rustc1_64,
+    /// Only the local part of the parent hash is hashed, and the local part
+    /// of the crate root is the crate id.
+    Rustc1_95,
+}
+
+/// The layout of the legacy symbol hash of a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolLayout {
+    /// `TyKind::FnDef` is variant 13 and `Tuple` 22, and `ExternAbi` hashes
+    /// its name.
+    Rustc1_95,
+}
+
+/// The version-specific details of a compiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Profile {
+    pub name: &'static str,
+    /// Version string the compiler was built with, hashed into crate ids.
+    pub rustc_version: &'static str,
+    pub rounds: SipRounds,
+    /// Snippets are compiled inside `fn main`, like the playground used to.
+    pub playground_wrapper: bool,
+    /// Width of `TypeId`s, 64 or 128.
+    pub type_id_bits: u32,
+    pub def_path_layout: DefPathLayout,
+    /// Layout of ADT hashes, if it is known for this compiler.
+    pub adt_layout: Option<AdtLayout>,
+    /// Layout of symbol hashes, if it is known for this compiler.
+    pub symbol_layout: Option<SymbolLayout>,
+}
+
+/// The nightly behind `examples/type_id.rs`.
+pub const RUSTC_1_64: Profile = Profile {
+    name: "1.64-nightly",
+    rustc_version: "1.64.0-nightly (7665c3543 2022-07-06)",
+    rounds: SipRounds::Sip24,
+    playground_wrapper: true,
+    type_id_bits: 64,
+    def_path_layout: DefPathLayout::Rustc1_64,
+    adt_layout: Some(AdtLayout::Rustc1_64),
+    symbol_layout: None,
+};
+
+/// The playground compiler behind `examples/type_id_128.rs`.
+pub const RUSTC_1_72: Profile = Profile {
+    name: "1.72-dev",
+    rustc_version: "1.72.0-dev",
+    rounds: SipRounds::Sip13,
+    playground_wrapper: false,
+    type_id_bits: 128,
+    def_path_layout: DefPathLayout::Rustc1_64,
+    adt_layout: None,
+    symbol_layout: None,
+};
+
+/// The stable release, checked against its legacy symbols, incremental
+/// dep graphs and `TypeId`s.
+pub const RUSTC_1_95: Profile = Profile {
+    name: "1.95",
+    rustc_version: "1.95.0 (59807616e 2026-04-14)",
+    rounds: SipRounds::Sip13,
+    playground_wrapper: false,
+    type_id_bits: 128,
+    def_path_layout: DefPathLayout::Rustc1_95,
+    adt_layout: Some(AdtLayout::Rustc1_95),
+    symbol_layout: Some(SymbolLayout::Rustc1_95),
+};
+
+/// All profiles, for lookup by name.
+pub static PROFILES: [Profile; 3] = [RUSTC_1_64, RUSTC_1_72, RUSTC_1_95];
+
+impl Profile {
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        PROFILES.iter().find(|profile| profile.name == name)
+    }
+
+    /// The crate id of a crate built by this compiler.
+    pub fn crate_id(
+        &'static self,
+        crate_name: &str,
+        is_exe: bool,
+        metadata: Vec<String>,
+    ) -> StableCrateId {
+        StableCrateId::new(crate_name, is_exe, metadata, self.rustc_version, self)
+    }
+
+    /// The path items of a snippet end up in: the crate root, or `fn main`
+    /// with the playground wrapper.
+    pub fn snippet_root(&self, crate_id: StableCrateId) -> DefPath {
+        let mut path = DefPath::new(crate_id);
+        if self.playground_wrapper {
+            path.push(DefPathData::ValueNs("main".to_owned()), 0);
+        }
+        path
+    }
+
+    /// The `TypeId` of `struct name { pub fields: usize, .. }` inside
+    /// `parent`, or `None` if the ADT layout of this compiler does not
+    /// model such a struct.
+    ///
+    /// The type of the fields is not part of the hash, only their names.
+    /// Fields are always public, private ones would hash their module.
+    pub fn struct_type_id(&self, parent: DefPathHash, name: &str, fields: &[&str]) -> Option<u128> {
+        let ty_hash = self.struct_ty_hash(parent, name, fields, &[])?;
+        Some(self.type_id(parent.crate_id, ty_hash))
+    }
+
+    /// The `TypeId` of the type whose stable hash is `ty_hash`.
+    pub fn type_id(&self, crate_id: StableCrateId, ty_hash: (u64, u64)) -> u128 {
+        let mut hasher = crate_id.hasher();
+        ty_hash.hash_stable(&mut HashingControls { hash_spans: false }, &mut hasher);
+        match self.type_id_bits {
+            64 => hasher.finish::<u64>() as u128,
+            _ => hasher.finish::<u128>(),
+        }
+    }
+
+    /// The stable hash of the type `name<args..>` for
+    /// `struct name<..> { pub fields: .., }` inside `parent`, or `None` if
+    /// the ADT layout of this compiler does not model such a struct.
+    ///
+    /// `args` are the stable hashes of the type arguments, from this method
+    /// for structs.  Lifetime and const arguments are not modelled.
+    pub fn struct_ty_hash(
+        &self,
+        parent: DefPathHash,
+        name: &str,
+        fields: &[&str],
+        args: &[(u64, u64)],
+    ) -> Option<(u64, u64)> {
+        let mut hcx = HashingControls { hash_spans: false };
+        let hasher = || parent.crate_id.hasher();
+        let struct_did = parent.child(&DefPathData::TypeNs(name.to_owned()), 0);
+        let adt_hash = match self.adt_layout? {
+            AdtLayout::Rustc1_64 if fields.len() != 1 || !args.is_empty() => return None,
+            AdtLayout::Rustc1_64 => {
+                let field_shuffle_seed = struct_did
+                    .crate_id
+                    .hash
+                    .wrapping_mul(3)
+                    .wrapping_add(struct_did.local);
+                let mut hasher = hasher();
+                struct_did.hash_stable(&mut hcx, &mut hasher);
+                // One variant: def id, no ctor, name, `VariantDiscr::Relative(0)`.
+                hasher.write_usize(1);
+                struct_did.hash_stable(&mut hcx, &mut hasher);
+                hasher.write_u8(0);
+                name.as_bytes().hash_stable(&mut hcx, &mut hasher);
+                hasher.write_isize(1);
+                hasher.write_u32(0);
+                // Fields: def id, name and visibility.
+                hasher.write_usize(fields.len());
+                for field in fields {
+                    let field_did = struct_did.child(&DefPathData::ValueNs((*field).to_owned()), 0);
+                    field_did.hash_stable(&mut hcx, &mut hasher);
+                    field.as_bytes().hash_stable(&mut hcx, &mut hasher);
+                    hasher.write_isize(0);
+                }
+                // `CtorKind::Fictive` and the variant flags.
+                hasher.write_isize(2);
+                hasher.write_u32(0);
+                // `AdtFlags::IS_STRUCT`
+                hasher.write_u32(4);
+                // `ReprOptions`: no int, align or pack, no flags.
+                hasher.write_u8(0);
+                hasher.write_u8(0);
+                hasher.write_u8(0);
+                hasher.write_u8(0);
+                hasher.write_u64(field_shuffle_seed);
+                hasher.finalize()
+            }
+            AdtLayout::Rustc1_95 => {
+                let field_shuffle_seed = struct_did
+                    .crate_id
+                    .hash
+                    .wrapping_mul(3)
+                    .wrapping_add(struct_did.local);
+                let mut hasher = hasher();
+                struct_did.hash_stable(&mut hcx, &mut hasher);
+                // One variant: def id, no ctor, name, `VariantDiscr::Relative(0)`.
+                hasher.write_usize(1);
+                struct_did.hash_stable(&mut hcx, &mut hasher);
+                hasher.write_u8(0);
+                name.as_bytes().hash_stable(&mut hcx, &mut hasher);
+                hasher.write_isize(1);
+                hasher.write_u32(0);
+                // Fields: def id, name, `Visibility::Public`, `Safety::Safe`
+                // and no default value.
+                hasher.write_usize(fields.len());
+                for field in fields {
+                    let field_did = struct_did.child(&DefPathData::ValueNs((*field).to_owned()), 0);
+                    field_did.hash_stable(&mut hcx, &mut hasher);
+                    field.as_bytes().hash_stable(&mut hcx, &mut hasher);
+                    hasher.write_isize(0);
+                    hasher.write_isize(1);
+                    hasher.write_u8(0);
+                }
+                // Not tainted by errors, and no variant flags.
+                hasher.write_u8(0);
+                hasher.write_u8(0);
+                // `AdtFlags::IS_STRUCT`
+                hasher.write_u32(4);
+                // `ReprOptions`: no int, align or pack.
+                hasher.write_u8(0);
+                hasher.write_u8(0);
+                hasher.write_u8(0);
+                hasher.write_u64(field_shuffle_seed);
+                hasher.finalize()
+            }
+        };
+        // The substs list of `GenericArgKind::Type`s, and `TyKind::Adt`.
+        let substs = args.iter().map(|ty| (1_isize, *ty)).collect::<Vec<_>>();
+        let substs_hash = hash_of(parent.crate_id, &mut hcx, &substs[..]);
+        Some(hash_of(
+            parent.crate_id,
+            &mut hcx,
+            (5_isize, adt_hash, substs_hash),
+        ))
+    }
+
+    /// The hash in the legacy symbol `_ZN..17h<hash>E` of `fn item()`, a
+    /// function without generics, arguments or return value, or `None` if
+    /// the symbol layout of this compiler is not known.
+    pub fn fn_symbol_hash(&self, item: DefPathHash) -> Option<u64> {
+        let mut hcx = HashingControls { hash_spans: false };
+        let crate_id = item.crate_id;
+        match self.symbol_layout? {
+            SymbolLayout::Rustc1_95 => {
+                let empty_list = hash_of(crate_id, &mut hcx, 0_usize);
+                // `TyKind::FnDef` of the item, without generic arguments.
+                let fn_def = hash_of(crate_id, &mut hcx, (13_isize, item, empty_list));
+                // `()`, the only type in `inputs_and_output`.
+                let unit = hash_of(crate_id, &mut hcx, (22_isize, empty_list));
+                let tys = hash_of(crate_id, &mut hcx, &[unit][..]);
+
+                let mut hasher = crate_id.hasher();
+                item.hash_stable(&mut hcx, &mut hasher);
+                fn_def.hash_stable(&mut hcx, &mut hasher);
+                // The `FnSig`: no C variadics, `Safety::Safe` and the Rust
+                // ABI, with no bound variables.
+                tys.hash_stable(&mut hcx, &mut hasher);
+                false.hash_stable(&mut hcx, &mut hasher);
+                hasher.write_isize(1);
+                hasher.write_str("Rust");
+                hasher.write_u32(u32::from_be_bytes(*b"ABI\0"));
+                empty_list.hash_stable(&mut hcx, &mut hasher);
+                // No generic arguments, and `InstanceKind::Item`.
+                empty_list.hash_stable(&mut hcx, &mut hasher);
+                hasher.write_isize(0);
+                Some(hasher.finish())
+            }
+        }
+    }
+}
+
+fn hash_of<T: HashStable<CTX>, CTX>(crate_id: StableCrateId, hcx: &mut CTX, val: T) -> (u64, u64) {
+    let mut hasher = crate_id.hasher();
+    val.hash_stable(hcx, &mut hasher);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Profile, PROFILES, RUSTC_1_64, RUSTC_1_72, RUSTC_1_95};
+    use crate::rustc::DefPath;
+
+    #[test]
+    fn rustc_1_64() {
+        // What `make_mod_id` and `type_id_of_struct` of the baseline
+        // `examples/type_id.rs` computed for its struct.  No 1.64 nightly was
+        // at hand to check them against the compiler.
+        let crate_id = RUSTC_1_64.crate_id("playground", true, vec!["a0ecb98bfb1b38c8".to_owned()]);
+        assert_eq!(crate_id.hash, 0x8fd11ef3f3a84b78);
+        let main = RUSTC_1_64.snippet_root(crate_id);
+        assert_eq!(main.to_string(), "crate::main");
+        assert_eq!(main.hash().local, 0x2ba8ad75b06aeda6);
+        assert_eq!(
+            RUSTC_1_64.struct_type_id(main.hash(), "Foo", &["x0000000000c0ffee"]),
+            Some(0x433f9d5605662ad5)
+        );
+        let fields = ["x0000000000c0ffee", "y"];
+        assert_eq!(RUSTC_1_64.struct_type_id(main.hash(), "Foo", &fields), None);
+        assert_eq!(RUSTC_1_64.struct_type_id(main.hash(), "Foo", &[]), None);
+    }
+
+    #[test]
+    fn rustc_1_72() {
+        // What `make_mod_id` of the baseline `examples/type_id_128.rs`
+        // computed for its crate.  No 1.72 build was at hand to check them.
+        let crate_id = RUSTC_1_72.crate_id("playground", true, vec!["051dac071847dbb3".to_owned()]);
+        assert_eq!(crate_id.hash, 0xc271afeb41224329);
+        let root = RUSTC_1_72.snippet_root(crate_id);
+        assert_eq!(root.to_string(), "crate");
+        assert_eq!(root.hash().local, 0x176f1518d8791cb8);
+        assert_eq!(RUSTC_1_72.struct_type_id(root.hash(), "Foo", &["x"]), None);
+    }
+
+    #[test]
+    fn rustc_1_95() {
+        // `pub fn foo() {}`, `pub fn bar_00c0ffee() {}` and `pub mod inner`
+        // of a library built by rustc 1.95.0 with
+        // `--crate-name symtest -C metadata=051dac071847dbb3`: the hashes
+        // were found in its incremental dep graph, the symbols with `nm`.
+        let crate_id = RUSTC_1_95.crate_id("symtest", false, vec!["051dac071847dbb3".to_owned()]);
+        assert_eq!(crate_id.hash, 0xdb7ac9fa2f31ed54);
+        let root = RUSTC_1_95.snippet_root(crate_id);
+        assert_eq!(root.hash().local, crate_id.hash);
+        let inner = DefPath::parse(crate_id, "mod inner").unwrap().hash();
+        assert_eq!(inner.local, 0xb929dcf704a90572);
+        let foo = DefPath::parse(crate_id, "fn foo").unwrap().hash();
+        assert_eq!(foo.local, 0xa0660423548ceb4b);
+        assert_eq!(RUSTC_1_95.fn_symbol_hash(foo), Some(0xce24323ec016dbed));
+        let bar = DefPath::parse(crate_id, "fn bar_00c0ffee").unwrap().hash();
+        assert_eq!(RUSTC_1_95.fn_symbol_hash(bar), Some(0x0cf4f5b158d2bce8));
+        assert_eq!(RUSTC_1_72.fn_symbol_hash(bar), None);
+    }
+
+    #[test]
+    fn rustc_1_95_type_ids() {
+        // `TypeId::of` transmuted to `u128` in a binary built by rustc 1.95.0
+        // with `--crate-name playground -C metadata=051dac071847dbb3`, from
+        // `pub struct Empty {}`, `pub struct OnePub { pub value: u8 }`,
+        // `pub struct Two { pub a: u8, pub b: u16 }`,
+        // `pub struct Foo { pub x0000000000c0ffee: usize }` and
+        // `pub mod inner { pub struct One { pub value: u8 } }`.
+        let crate_id = RUSTC_1_95.crate_id("playground", true, vec!["051dac071847dbb3".to_owned()]);
+        assert_eq!(crate_id.hash, 0x6c7105f98e8e2a7d);
+        let root = RUSTC_1_95.snippet_root(crate_id).hash();
+        let type_id = |parent, name, fields: &[&str]| {
+            RUSTC_1_95.struct_type_id(parent, name, fields).unwrap()
+        };
+        assert_eq!(
+            type_id(root, "Empty", &[]),
+            0x978bc3b5e958312b861f937ca1124c94
+        );
+        assert_eq!(
+            type_id(root, "OnePub", &["value"]),
+            0xef0761c5f37826f7100b0f2d43b0cb25
+        );
+        assert_eq!(
+            type_id(root, "Two", &["a", "b"]),
+            0xd13f1afa4a75be5188fe3d9daaa3f0be
+        );
+        assert_eq!(
+            type_id(root, "Foo", &["x0000000000c0ffee"]),
+            0x0a570fe8dab1346e8577fe778e34a3f5
+        );
+        let inner = DefPath::parse(crate_id, "mod inner").unwrap().hash();
+        assert_eq!(
+            type_id(inner, "One", &["value"]),
+            0x2043eaaaba81251258c64b0fa5df7170
+        );
+    }
+
+    #[test]
+    fn generic_args() {
+        // The same binary as `rustc_1_95_type_ids`, with
+        // `pub struct G<T> { pub value: T }` and
+        // `pub struct Pair<A, B> { pub first: A, pub second: B }`.
+        let crate_id = RUSTC_1_95.crate_id("playground", true, vec!["051dac071847dbb3".to_owned()]);
+        let root = RUSTC_1_95.snippet_root(crate_id).hash();
+        let ty = |name, fields: &[&str], args: &[(u64, u64)]| {
+            RUSTC_1_95.struct_ty_hash(root, name, fields, args).unwrap()
+        };
+        let type_id = |ty_hash| RUSTC_1_95.type_id(crate_id, ty_hash);
+        let (empty, one) = (ty("Empty", &[], &[]), ty("OnePub", &["value"], &[]));
+        assert_eq!(
+            type_id(ty("G", &["value"], &[empty])),
+            0x00eaefeb99a75f49a41a7f06b003808b
+        );
+        assert_eq!(
+            type_id(ty("G", &["value"], &[one])),
+            0xfc4d2d6654c4a3330826629c8dfaee57
+        );
+        let g_empty = ty("G", &["value"], &[empty]);
+        assert_eq!(
+            type_id(ty("G", &["value"], &[g_empty])),
+            0xccb67d0a893aca3fcb087ca084ccb95f
+        );
+        assert_eq!(
+            type_id(ty("Pair", &["first", "second"], &[empty, one])),
+            0x0f743cd2f34d4c9f9cf776da3281c36c
+        );
+        assert_eq!(RUSTC_1_72.struct_ty_hash(root, "G", &[], &[empty]), None);
+    }
+
+    #[test]
+    fn by_name() {
+        for profile in &PROFILES {
+            assert_eq!(Profile::by_name(profile.name), Some(profile));
+        }
+        assert_eq!(Profile::by_name("1.0"), None);
+    }
+}
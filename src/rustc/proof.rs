@@ -0,0 +1,440 @@
+//! Cargo projects proving a `TypeId` collision.
+//!
+//! The project holds the colliding items and a `main` asserting that two
+//! types have the same `TypeId`.  The crate id must be the one the search
+//! used, so it is built either with `cargo run`, when the metadata came from
+//! `CargoUnit` for the same package, or with the `rustc` command in
+//! `build.sh` passing the searched `-C metadata` values.
+//!
+//! `ProofCrate::verify` builds and runs a written proof with the local
+//! compiler, and reads back the crate id it used to tell a false collision
+//! from a profile that does not match that compiler.
+
+use super::cargo::{crate_ids_in_target, local_rustc, local_rustc_version};
+use super::def_path::{DefPathData, DisambiguatedDefPathData, StableCrateId};
+use crate::error::{Error, Result};
+use core::fmt;
+use std::path::{Path, PathBuf};
+
+/// A binary crate asserting `TypeId::of::<A>() == TypeId::of::<B>()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofCrate {
+    pub crate_name: String,
+    pub package_version: String,
+    pub metadata: Vec<String>,
+    /// Put the items inside `fn main`, like the playground did.
+    pub in_main: bool,
+    /// Declarations of the colliding items.
+    pub items: String,
+    /// Paths of the two types, relative to the items.
+    pub types: [String; 2],
+    /// A comment put on top of `main.rs`.
+    pub note: String,
+    /// The `TypeId` the model gave both types and its width in bits, also
+    /// asserted when set, so the proof only holds if the model does.
+    pub type_id: Option<(u128, u32)>,
+}
+
+impl ProofCrate {
+    pub fn new(crate_name: &str, metadata: Vec<String>, in_main: bool) -> Self {
+        Self {
+            crate_name: crate_name.to_owned(),
+            package_version: "0.1.0".to_owned(),
+            metadata,
+            in_main,
+            items: String::new(),
+            types: Default::default(),
+            note: String::new(),
+            type_id: None,
+        }
+    }
+
+    pub fn with_package_version(mut self, version: &str) -> Self {
+        self.package_version = version.to_owned();
+        self
+    }
+
+    /// The proof for `items` with the types `type_a` and `type_b`.
+    pub fn with_items(mut self, items: &str, type_a: &str, type_b: &str, note: &str) -> Self {
+        self.items = items.to_owned();
+        self.types = [type_a.to_owned(), type_b.to_owned()];
+        self.note = note.to_owned();
+        self
+    }
+
+    /// Also assert that the first type has the `bits`-bit `TypeId` `type_id`.
+    pub fn with_type_id(mut self, type_id: u128, bits: u32) -> Self {
+        self.type_id = Some((type_id, bits));
+        self
+    }
+
+    pub fn cargo_toml(&self) -> String {
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"{}\"\nedition = \"2021\"\n\n[dependencies]\n",
+            self.crate_name, self.package_version
+        )
+    }
+
+    pub fn main_rs(&self) -> String {
+        let mut main = String::new();
+        for line in self.note.lines() {
+            match line {
+                "" => main += "//\n",
+                line => main += &format!("// {}\n", line),
+            }
+        }
+        main += "\nuse std::any::TypeId;\n\n";
+        let mut assert = format!(
+            "assert_eq!(TypeId::of::<{}>(), TypeId::of::<{}>());\n",
+            self.types[0], self.types[1]
+        );
+        if let Some((type_id, bits)) = self.type_id {
+            assert += &format!(
+                "    assert_eq!(unsafe {{ std::mem::transmute::<TypeId, u{}>(TypeId::of::<{}>()) }}, {:#x});\n",
+                bits, self.types[0], type_id
+            );
+        }
+        let indent = if self.in_main { "    " } else { "" };
+        let items = self
+            .items
+            .lines()
+            .map(|line| format!("{}{}\n", indent, line))
+            .collect::<String>();
+        if self.in_main {
+            main += "fn main() {\n";
+            main += &items;
+            main += &format!("\n    {}", assert);
+        } else {
+            main += &items;
+            main += &format!("\nfn main() {{\n    {}", assert);
+        }
+        main += "    println!(\"the TypeIds are equal\");\n}\n";
+        main
+    }
+
+    /// `rustc` arguments building `src/main.rs` into `target/proof` with the
+    /// searched crate name and metadata.
+    pub fn rustc_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--edition=2021".to_owned(),
+            "--crate-name".to_owned(),
+            self.crate_name.clone(),
+            "--crate-type".to_owned(),
+            "bin".to_owned(),
+        ];
+        for metadata in &self.metadata {
+            args.extend(["-C".to_owned(), format!("metadata={}", metadata)]);
+        }
+        args.extend([
+            "src/main.rs".to_owned(),
+            "-o".to_owned(),
+            "target/proof".to_owned(),
+        ]);
+        args
+    }
+
+    /// Write the project into `dir`, which is created if needed.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir.join("src"))?;
+        std::fs::write(dir.join("Cargo.toml"), self.cargo_toml())?;
+        std::fs::write(dir.join("src/main.rs"), self.main_rs())?;
+        let script = format!(
+            "#!/bin/sh\nset -e\ncd \"$(dirname \"$0\")\"\nmkdir -p target\n${{RUSTC:-rustc}} {}\n./target/proof\n",
+            self.rustc_args().join(" ")
+        );
+        std::fs::write(dir.join("build.sh"), script)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(dir.join("build.sh"), std::fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+
+    /// Build the proof written into `dir` with the local `rustc` and run it.
+    ///
+    /// `crate_id` is the one the search hashed with.  The compiler's own
+    /// crate id is read from its incremental directory, so a proof failing
+    /// because the profile does not model the local compiler is flagged as
+    /// such.  Only failing to start `rustc` is an error.
+    pub fn verify(&self, dir: &Path, crate_id: StableCrateId) -> Result<Verification> {
+        let rustc_version = local_rustc_version()?;
+        // The same layout cargo uses, for `crate_ids_in_target`.
+        let incremental = dir.join("target/debug/incremental");
+        let _ = std::fs::remove_dir_all(&incremental);
+        std::fs::create_dir_all(&incremental)?;
+        let build = local_rustc()
+            .current_dir(dir)
+            .args(self.rustc_args())
+            .arg("-C")
+            .arg("incremental=target/debug/incremental")
+            .output()?;
+        let mut output = String::from_utf8_lossy(&build.stderr).into_owned();
+
+        let local_id = match crate_ids_in_target(&dir.join("target"), &self.crate_name)?[..] {
+            [id] => Some(id),
+            _ => None,
+        };
+        let mut mismatches = Vec::new();
+        if let Some(id) = local_id.filter(|id| *id != crate_id.hash) {
+            mismatches.push(format!(
+                "rustc {} gave the crate id {:016x}, profile {} hashed {:016x}",
+                rustc_version, id, crate_id.profile.name, crate_id.hash
+            ));
+        }
+        if rustc_version != crate_id.profile.rustc_version {
+            mismatches.push(format!(
+                "profile {} models rustc {}, not {}",
+                crate_id.profile.name, crate_id.profile.rustc_version, rustc_version
+            ));
+        }
+
+        let mut holds = false;
+        if build.status.success() {
+            let run = std::process::Command::new(dir.join("target/proof")).output()?;
+            holds = run.status.success();
+            output += &String::from_utf8_lossy(&run.stdout);
+            output += &String::from_utf8_lossy(&run.stderr);
+        }
+        Ok(Verification {
+            rustc_version,
+            crate_id: local_id,
+            mismatches,
+            holds,
+            output,
+        })
+    }
+}
+
+/// The proofs of an adapter colliding items declared under modules: the
+/// template crate, the directory they go to and whether they are verified.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Proofs {
+    /// The segments from the snippet root to the items.
+    parent_mods: Vec<String>,
+    dir: Option<(PathBuf, ProofCrate)>,
+    verify: bool,
+}
+
+impl Proofs {
+    /// Write proofs built from `template` into `dir`, with the items under
+    /// `parent`, which must be made of modules.
+    pub fn set_dir(
+        &mut self,
+        dir: PathBuf,
+        template: ProofCrate,
+        parent: &[DisambiguatedDefPathData],
+    ) -> Result<()> {
+        self.parent_mods = module_names(parent)?;
+        self.dir = Some((dir, template));
+        Ok(())
+    }
+
+    pub fn set_verify(&mut self) {
+        self.verify = true;
+    }
+
+    /// The template crate, if proofs are written.
+    pub fn template(&self) -> Option<&ProofCrate> {
+        self.dir.as_ref().map(|(_, template)| template)
+    }
+
+    /// The path from the snippet root to the items, like `a::b::`.
+    pub fn prefix(&self) -> String {
+        self.parent_mods
+            .iter()
+            .map(|name| format!("{}::", name))
+            .collect()
+    }
+
+    /// `items` declared inside the parent modules.
+    pub fn in_modules(&self, items: &str) -> String {
+        in_modules(items, &self.parent_mods)
+    }
+
+    /// Write the proof built by `proof` into a directory named by the crate
+    /// and `hash`, and verify it against `crate_id` if asked, returning the
+    /// text added to the collision report.  Failures are printed, the
+    /// search goes on.
+    pub fn write_for_report(
+        &self,
+        hash: u64,
+        crate_id: StableCrateId,
+        proof: impl FnOnce() -> Option<ProofCrate>,
+    ) -> String {
+        let (Some((dir, template)), Some(proof)) = (&self.dir, proof()) else {
+            return String::new();
+        };
+        let dir = dir.join(format!("{}-{:016x}", template.crate_name, hash));
+        if let Err(e) = proof.write(&dir) {
+            eprintln!("failed to write the proof crate: {}", e);
+            return String::new();
+        }
+        let mut text = format!(" proof {}", dir.display());
+        if self.verify {
+            match proof.verify(&dir, crate_id) {
+                Ok(verification) => text += &format!(" ({})", verification),
+                Err(e) => eprintln!("failed to verify the proof crate: {}", e),
+            }
+        }
+        text
+    }
+}
+
+/// The names of the modules making up `path`, which must hold nothing else.
+fn module_names(path: &[DisambiguatedDefPathData]) -> Result<Vec<String>> {
+    path.iter()
+        .map(|segment| match (&segment.data, segment.disambiguator) {
+            (DefPathData::TypeNs(name), 0) => Ok(name.clone()),
+            _ => Err(Error::Invalid(format!(
+                "proofs need the parent to be made of modules, not {}",
+                segment
+            ))),
+        })
+        .collect()
+}
+
+/// `items` declared inside the nested public modules `mods`.
+fn in_modules(items: &str, mods: &[String]) -> String {
+    let mut items = items.to_owned();
+    for name in mods.iter().rev() {
+        let inner = items
+            .lines()
+            .map(|line| match line {
+                "" => "\n".to_owned(),
+                line => format!("    {}\n", line),
+            })
+            .collect::<String>();
+        items = format!("pub mod {} {{\n{}}}", name, inner);
+    }
+    items
+}
+
+/// The outcome of `ProofCrate::verify`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Verification {
+    /// Version of the local compiler, like `1.95.0 (59807616e 2026-04-14)`.
+    pub rustc_version: String,
+    /// Crate id the local compiler gave the proof, if it could be read.
+    pub crate_id: Option<u64>,
+    /// Differences between the hashing model and the local compiler.
+    pub mismatches: Vec<String>,
+    /// The proof built and its assertion held.
+    pub holds: bool,
+    /// Output of the compiler and of the proof.
+    pub output: String,
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.holds {
+            true => write!(f, "holds with rustc {}", self.rustc_version)?,
+            false => write!(f, "fails with rustc {}", self.rustc_version)?,
+        }
+        for mismatch in &self.mismatches {
+            write!(f, "; {}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProofCrate;
+    use crate::rustc::{local_rustc_version, StableCrateId, RUSTC_1_72, RUSTC_1_95};
+
+    #[test]
+    fn layout() {
+        let proof = ProofCrate::new("playground", vec!["051dac071847dbb3".to_owned()], true)
+            .with_items(
+                "mod a { pub struct S; }\nmod b { pub struct S; }",
+                "a::S",
+                "b::S",
+                "a and b\n\ncollide",
+            );
+        assert_eq!(
+            proof.main_rs(),
+            "// a and b
+//
+// collide
+
+use std::any::TypeId;
+
+fn main() {
+    mod a { pub struct S; }
+    mod b { pub struct S; }
+
+    assert_eq!(TypeId::of::<a::S>(), TypeId::of::<b::S>());
+    println!(\"the TypeIds are equal\");
+}
+"
+        );
+        assert!(proof.cargo_toml().contains("name = \"playground\""));
+        assert!(proof.clone().with_type_id(0xc0ffee, 64).main_rs().contains(
+            "    assert_eq!(unsafe { std::mem::transmute::<TypeId, u64>(TypeId::of::<a::S>()) }, 0xc0ffee);\n"
+        ));
+        assert!(proof
+            .rustc_args()
+            .join(" ")
+            .contains("--crate-name playground --crate-type bin -C metadata=051dac071847dbb3"));
+    }
+
+    #[test]
+    fn verify_with_local_rustc() {
+        // The proofs assert the TypeIds modelled by `RUSTC_1_95`, so they
+        // only hold with that compiler.
+        match local_rustc_version() {
+            Ok(version) if version == RUSTC_1_95.rustc_version => {}
+            Ok(version) => return eprintln!("skipped, the local rustc is {}", version),
+            Err(e) => return eprintln!("skipped, no local rustc: {}", e),
+        }
+        let dir = std::env::temp_dir().join(format!("proof-crate-{}", std::process::id()));
+        let metadata = vec!["051dac071847dbb3".to_owned()];
+        let verify = |crate_id: StableCrateId, modelled: StableCrateId| {
+            let root = RUSTC_1_95.snippet_root(modelled).hash();
+            let type_id = RUSTC_1_95
+                .struct_type_id(root, "Foo", &["x0000000000c0ffee"])
+                .unwrap();
+            let proof = ProofCrate::new("playground", metadata.clone(), false)
+                .with_items(
+                    "#[allow(dead_code)]\npub struct Foo {\n    pub x0000000000c0ffee: usize,\n}",
+                    "Foo",
+                    "Foo",
+                    "",
+                )
+                .with_type_id(type_id, RUSTC_1_95.type_id_bits);
+            proof.write(&dir).unwrap();
+            let verification = proof.verify(&dir, crate_id);
+            std::fs::remove_dir_all(&dir).unwrap();
+            verification.unwrap()
+        };
+
+        let crate_id = RUSTC_1_95.crate_id("playground", true, metadata.clone());
+        let verification = verify(crate_id, crate_id);
+        assert!(verification.holds, "{}", verification.output);
+        assert_eq!(verification.crate_id, Some(crate_id.hash));
+        assert_eq!(verification.mismatches, Vec::<String>::new());
+
+        // The TypeId hashes the crate id, so a model with another crate id
+        // fails, and the verification tells why.
+        let other = RUSTC_1_95.crate_id("playground", true, vec!["0000000000000000".to_owned()]);
+        let verification = verify(other, other);
+        assert!(!verification.holds);
+        assert_eq!(verification.crate_id, Some(crate_id.hash));
+        assert_eq!(verification.mismatches.len(), 1);
+        assert!(verification.mismatches[0].contains("gave the crate id 6c7105f98e8e2a7d"));
+
+        // Hashing the crate id with another profile is reported too.
+        let version = local_rustc_version().unwrap();
+        let wrong = StableCrateId::new("playground", true, metadata.clone(), &version, &RUSTC_1_72);
+        let verification = verify(wrong, crate_id);
+        assert!(verification.holds, "{}", verification.output);
+        assert_eq!(
+            verification.mismatches,
+            vec![format!(
+                "profile 1.72-dev models rustc {}, not {}",
+                RUSTC_1_72.rustc_version, version
+            )]
+        );
+    }
+}
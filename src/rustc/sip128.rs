@@ -1,12 +1,10 @@
-//! This is a copy of `core::hash::sip` adapted to providing 128 bit hashes.
+//! This is a copy of `core::hash::sip` adapted to providing 128 bit hashes,
+//! taken from `rustc_data_structures` and ported to stable Rust.
 
 use std::hash::Hasher;
 use std::mem::{self, MaybeUninit};
 use std::ptr;
 
-// #[cfg(test)]
-// mod tests;
-
 // The SipHash algorithm operates on 8-byte chunks.
 const ELEM_SIZE: usize = mem::size_of::<u64>();
 
@@ -48,6 +46,7 @@ pub struct SipHasher128 {
     buf: [MaybeUninit<u64>; BUFFER_WITH_SPILL_CAPACITY], // unprocessed bytes le
     state: State, // hash State
     processed: usize, // how many bytes we've processed
+    rounds: SipRounds,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -64,7 +63,9 @@ struct State {
 }
 
 macro_rules! compress {
-    ($state:expr) => {{ compress!($state.v0, $state.v1, $state.v2, $state.v3) }};
+    ($state:expr) => {{
+        compress!($state.v0, $state.v1, $state.v2, $state.v3)
+    }};
     ($v0:expr, $v1:expr, $v2:expr, $v3:expr) => {{
         $v0 = $v0.wrapping_add($v1);
         $v1 = $v1.rotate_left(13);
@@ -183,9 +184,14 @@ unsafe fn copy_nonoverlapping_small(src: *const u8, dst: *mut u8, count: usize)
 impl SipHasher128 {
     #[inline]
     pub fn new_with_keys(key0: u64, key1: u64) -> SipHasher128 {
+        Self::new_with_rounds(key0, key1, SipRounds::default())
+    }
+
+    #[inline]
+    pub fn new_with_rounds(key0: u64, key1: u64, rounds: SipRounds) -> SipHasher128 {
         let mut hasher = SipHasher128 {
             nbuf: 0,
-            buf: MaybeUninit::uninit_array(),
+            buf: [MaybeUninit::uninit(); BUFFER_WITH_SPILL_CAPACITY],
             state: State {
                 v0: key0 ^ 0x736f6d6570736575,
                 // The XOR with 0xee is only done on 128-bit algorithm version.
@@ -194,6 +200,7 @@ impl SipHasher128 {
                 v3: key1 ^ 0x7465646279746573,
             },
             processed: 0,
+            rounds,
         };
 
         unsafe {
@@ -204,6 +211,15 @@ impl SipHasher128 {
         hasher
     }
 
+    /// Prefix-free write of a string, `Hasher::write_str` is not stable yet.
+    #[inline]
+    pub fn write_str(&mut self, s: &str) {
+        // This hasher works byte-wise, and `0xFF` cannot show up in a `str`,
+        // so just hashing the one extra byte is enough to be prefix-free.
+        self.slice_write(s.as_bytes());
+        self.short_write([0xFF]);
+    }
+
     #[inline]
     pub fn short_write<const LEN: usize>(&mut self, bytes: [u8; LEN]) {
         let nbuf = self.nbuf;
@@ -250,7 +266,7 @@ impl SipHasher128 {
             for i in 0..BUFFER_CAPACITY {
                 let elem = self.buf.get_unchecked(i).assume_init().to_le();
                 self.state.v3 ^= elem;
-                Sip13Rounds::c_rounds(&mut self.state);
+                self.rounds.c_rounds(&mut self.state);
                 self.state.v0 ^= elem;
             }
 
@@ -265,7 +281,11 @@ impl SipHasher128 {
             // This function should only be called when the write fills the buffer.
             // Therefore, when LEN == 1, the new `self.nbuf` must be zero.
             // LEN is statically known, so the branch is optimized away.
-            self.nbuf = if LEN == 1 { 0 } else { nbuf + LEN - BUFFER_SIZE };
+            self.nbuf = if LEN == 1 {
+                0
+            } else {
+                nbuf + LEN - BUFFER_SIZE
+            };
             self.processed += BUFFER_SIZE;
         }
     }
@@ -332,7 +352,7 @@ impl SipHasher128 {
             for i in 0..last {
                 let elem = self.buf.get_unchecked(i).assume_init().to_le();
                 self.state.v3 ^= elem;
-                Sip13Rounds::c_rounds(&mut self.state);
+                self.rounds.c_rounds(&mut self.state);
                 self.state.v0 ^= elem;
             }
 
@@ -343,9 +363,11 @@ impl SipHasher128 {
             let extra_bytes_left = input_left % ELEM_SIZE;
 
             for _ in 0..elems_left {
-                let elem = (msg.as_ptr().add(processed) as *const u64).read_unaligned().to_le();
+                let elem = (msg.as_ptr().add(processed) as *const u64)
+                    .read_unaligned()
+                    .to_le();
                 self.state.v3 ^= elem;
-                Sip13Rounds::c_rounds(&mut self.state);
+                self.rounds.c_rounds(&mut self.state);
                 self.state.v0 ^= elem;
                 processed += ELEM_SIZE;
             }
@@ -374,12 +396,12 @@ impl SipHasher128 {
         for i in 0..last {
             let elem = unsafe { self.buf.get_unchecked(i).assume_init().to_le() };
             state.v3 ^= elem;
-            Sip13Rounds::c_rounds(&mut state);
+            self.rounds.c_rounds(&mut state);
             state.v0 ^= elem;
         }
 
         // Get remaining partial element.
-        let elem = if self.nbuf % ELEM_SIZE != 0 {
+        let elem = if !self.nbuf.is_multiple_of(ELEM_SIZE) {
             unsafe {
                 // Ensure element is initialized by writing zero bytes. At most
                 // `ELEM_SIZE - 1` are required given the above check. It's safe
@@ -398,15 +420,15 @@ impl SipHasher128 {
         let b: u64 = ((length as u64 & 0xff) << 56) | elem;
 
         state.v3 ^= b;
-        Sip13Rounds::c_rounds(&mut state);
+        self.rounds.c_rounds(&mut state);
         state.v0 ^= b;
 
         state.v2 ^= 0xee;
-        Sip13Rounds::d_rounds(&mut state);
+        self.rounds.d_rounds(&mut state);
         let _0 = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
 
         state.v1 ^= 0xdd;
-        Sip13Rounds::d_rounds(&mut state);
+        self.rounds.d_rounds(&mut state);
         let _1 = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
 
         (_0, _1)
@@ -469,32 +491,120 @@ impl Hasher for SipHasher128 {
         self.slice_write(msg);
     }
 
-    #[inline]
-    fn write_str(&mut self, s: &str) {
-        // This hasher works byte-wise, and `0xFF` cannot show up in a `str`,
-        // so just hashing the one extra byte is enough to be prefix-free.
-        self.write(s.as_bytes());
-        self.write_u8(0xFF);
-    }
-
     fn finish(&self) -> u64 {
         panic!("SipHasher128 cannot provide valid 64 bit hashes")
     }
 }
 
-#[derive(Debug, Clone, Default)]
-struct Sip13Rounds;
+/// The SipHash variant.  rustc switched its `StableHasher` from SipHash-2-4
+/// to SipHash-1-3 in early 2023, so hashes of older compilers, like 1.64,
+/// need `Sip24`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SipRounds {
+    #[default]
+    Sip13,
+    Sip24,
+}
 
-impl Sip13Rounds {
+impl SipRounds {
     #[inline]
-    fn c_rounds(state: &mut State) {
+    fn c_rounds(self, state: &mut State) {
         compress!(state);
+        if self == SipRounds::Sip24 {
+            compress!(state);
+        }
     }
 
     #[inline]
-    fn d_rounds(state: &mut State) {
+    fn d_rounds(self, state: &mut State) {
         compress!(state);
         compress!(state);
         compress!(state);
+        if self == SipRounds::Sip24 {
+            compress!(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SipHasher128, SipRounds};
+    use std::hash::Hasher;
+
+    /// Unbuffered SipHash-c-d with 128-bit output over the whole message.
+    fn reference(msg: &[u8], c: usize, d: usize) -> (u64, u64) {
+        let mut v = [
+            0x736f6d6570736575_u64,
+            0x646f72616e646f6d ^ 0xee,
+            0x6c7967656e657261,
+            0x7465646279746573,
+        ];
+        let round = |v: &mut [u64; 4]| {
+            let [mut v0, mut v1, mut v2, mut v3] = *v;
+            compress!(v0, v1, v2, v3);
+            *v = [v0, v1, v2, v3];
+        };
+        let mut padded = msg.to_vec();
+        padded.resize(msg.len() / 8 * 8 + 7, 0);
+        padded.push(msg.len() as u8);
+        for chunk in padded.chunks_exact(8) {
+            let m = u64::from_le_bytes(chunk.try_into().unwrap());
+            v[3] ^= m;
+            for _ in 0..c {
+                round(&mut v);
+            }
+            v[0] ^= m;
+        }
+        let mut out = [0; 2];
+        for (ii, flag) in [(0, 0xee), (1, 0xdd)] {
+            v[if ii == 0 { 2 } else { 1 }] ^= flag;
+            for _ in 0..d {
+                round(&mut v);
+            }
+            out[ii] = v[0] ^ v[1] ^ v[2] ^ v[3];
+        }
+        (out[0], out[1])
+    }
+
+    #[test]
+    fn matches_reference() {
+        let data = (0..=255_u8).collect::<Vec<_>>();
+        for (rounds, c, d) in [(SipRounds::Sip13, 1, 3), (SipRounds::Sip24, 2, 4)] {
+            for len in 0..200 {
+                let mut hasher = SipHasher128::new_with_rounds(0, 0, rounds);
+                hasher.write(&data[..len]);
+                let expected = reference(&data[..len], c, d);
+                assert_eq!(hasher.finish128(), expected, "{:?} length {}", rounds, len);
+            }
+        }
+        // Values from rustc's own implementation, before and after the switch
+        // to SipHash-1-3.
+        let mut hasher = SipHasher128::new_with_keys(0, 0);
+        hasher.write(&data[..65]);
+        assert_eq!(hasher.finish128(), (0xd3ab640796f22b7c, 0xac07f543b7b04021));
+        let mut hasher = SipHasher128::new_with_rounds(0, 0, SipRounds::Sip24);
+        hasher.write(&data[..65]);
+        assert_eq!(hasher.finish128(), (0x7687c4fb9d15ff64, 0xadeaec6c94fe9b12));
+    }
+
+    #[test]
+    fn split_writes() {
+        let data = (0..=255_u8).collect::<Vec<_>>();
+        for split in [1, 3, 7, 8, 9, 63, 64, 65] {
+            let mut hasher = SipHasher128::new_with_keys(0, 0);
+            for chunk in data[..150].chunks(split) {
+                hasher.write(chunk);
+            }
+            hasher.short_write([0, 1, 2, 3]);
+            hasher.write_u8(4);
+            let mut expected = data[..150].to_vec();
+            expected.extend([0, 1, 2, 3, 4]);
+            assert_eq!(
+                hasher.finish128(),
+                reference(&expected, 1, 3),
+                "split {}",
+                split
+            );
+        }
     }
 }
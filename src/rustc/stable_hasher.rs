@@ -1,154 +1,16 @@
-use crate::sip128::SipHasher128;
-// use rustc_index::bit_set::{self, BitSet};
-// use rustc_index::{Idx, IndexVec};
-// use smallvec::SmallVec;
+//! `StableHasher` and the `HashStable` impls of `rustc_data_structures`.
+//!
+//! The impls that needed `specialization` are gone: rustc hashes `[u8]`
+//! with a single `write`, the generic `[T]` impl writes the bytes one by one,
+//! which gives the same byte stream and so the same hash.
+
+use super::hashes::{Hash128, Hash64};
+use super::sip128::{SipHasher128, SipRounds};
 use std::fmt;
 use std::hash::{BuildHasher, Hash, Hasher};
 use std::marker::PhantomData;
 use std::mem;
 
-// #[cfg(test)]
-// mod tests;
-
-#[allow(dead_code)]
-mod hashes {
-    //! rustc encodes a lot of hashes. If hashes are stored as `u64` or `u128`, a `derive(Encodable)`
-    //! will apply varint encoding to the hashes, which is less efficient than directly encoding the 8
-    //! or 16 bytes of the hash.
-    //!
-    //! The types in this module represent 64-bit or 128-bit hashes produced by a `StableHasher`.
-    //! `Hash64` and `Hash128` expose some utilty functions to encourage users to not extract the inner
-    //! hash value as an integer type and accidentally apply varint encoding to it.
-    //!
-    //! In contrast with `Fingerprint`, users of these types cannot and should not attempt to construct
-    //! and decompose these types into constitutent pieces. The point of these types is only to
-    //! connect the fact that they can only be produced by a `StableHasher` to their
-    //! `Encode`/`Decode` impls.
-
-    use crate::stable_hasher::{StableHasher, StableHasherResult};
-    // use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
-    use std::fmt;
-    use std::ops::BitXorAssign;
-
-    #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
-    pub struct Hash64 {
-        inner: u64,
-    }
-
-    impl Hash64 {
-        pub const ZERO: Hash64 = Hash64 { inner: 0 };
-
-        #[inline]
-        pub(crate) fn new(n: u64) -> Self {
-            Self { inner: n }
-        }
-
-        #[inline]
-        pub fn as_u64(self) -> u64 {
-            self.inner
-        }
-    }
-
-    impl BitXorAssign<u64> for Hash64 {
-        #[inline]
-        fn bitxor_assign(&mut self, rhs: u64) {
-            self.inner ^= rhs;
-        }
-    }
-
-    // impl<S: Encoder> Encodable<S> for Hash64 {
-    //     #[inline]
-    //     fn encode(&self, s: &mut S) {
-    //         s.emit_raw_bytes(&self.inner.to_le_bytes());
-    //     }
-    // }
-
-    // impl<D: Decoder> Decodable<D> for Hash64 {
-    //     #[inline]
-    //     fn decode(d: &mut D) -> Self {
-    //         Self { inner: u64::from_le_bytes(d.read_raw_bytes(8).try_into().unwrap()) }
-    //     }
-    // }
-
-    impl StableHasherResult for Hash64 {
-        #[inline]
-        fn finish(hasher: StableHasher) -> Self {
-            Self { inner: hasher.finalize().0 }
-        }
-    }
-
-    impl fmt::Debug for Hash64 {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            self.inner.fmt(f)
-        }
-    }
-
-    impl fmt::LowerHex for Hash64 {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            fmt::LowerHex::fmt(&self.inner, f)
-        }
-    }
-
-    #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
-    pub struct Hash128 {
-        inner: u128,
-    }
-
-    impl Hash128 {
-        #[inline]
-        pub fn truncate(self) -> Hash64 {
-            Hash64 { inner: self.inner as u64 }
-        }
-
-        #[inline]
-        pub fn wrapping_add(self, other: Self) -> Self {
-            Self { inner: self.inner.wrapping_add(other.inner) }
-        }
-
-        #[inline]
-        pub fn as_u128(self) -> u128 {
-            self.inner
-        }
-    }
-
-    // impl<S: Encoder> Encodable<S> for Hash128 {
-    //     #[inline]
-    //     fn encode(&self, s: &mut S) {
-    //         s.emit_raw_bytes(&self.inner.to_le_bytes());
-    //     }
-    // }
-
-    // impl<D: Decoder> Decodable<D> for Hash128 {
-    //     #[inline]
-    //     fn decode(d: &mut D) -> Self {
-    //         Self { inner: u128::from_le_bytes(d.read_raw_bytes(16).try_into().unwrap()) }
-    //     }
-    // }
-
-    impl StableHasherResult for Hash128 {
-        #[inline]
-        fn finish(hasher: StableHasher) -> Self {
-            let (_0, _1) = hasher.finalize();
-            Self { inner: u128::from(_0) | (u128::from(_1) << 64) }
-        }
-    }
-
-    impl fmt::Debug for Hash128 {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            self.inner.fmt(f)
-        }
-    }
-
-    impl fmt::LowerHex for Hash128 {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            fmt::LowerHex::fmt(&self.inner, f)
-        }
-    }
-
-}
-
-pub use hashes::{Hash128, Hash64};
-
 /// When hashing something that ends up affecting properties like symbol names,
 /// we want these symbol names to be calculated independently of other factors
 /// like what architecture you're compiling *from*.
@@ -156,9 +18,9 @@ pub use hashes::{Hash128, Hash64};
 /// To that end we always convert integers to little-endian format before
 /// hashing and the architecture dependent `isize` and `usize` types are
 /// extended to 64 bits if needed.
+#[derive(Clone)]
 pub struct StableHasher {
     state: SipHasher128,
-    pub debug: bool,
 }
 
 impl fmt::Debug for StableHasher {
@@ -174,9 +36,14 @@ pub trait StableHasherResult: Sized {
 impl StableHasher {
     #[inline]
     pub fn new() -> Self {
+        Self::new_with_rounds(SipRounds::default())
+    }
+
+    /// A hasher of a compiler that uses the SipHash variant `rounds`.
+    #[inline]
+    pub fn new_with_rounds(rounds: SipRounds) -> Self {
         StableHasher {
-            state: SipHasher128::new_with_keys(0, 0),
-            debug: false,
+            state: SipHasher128::new_with_rounds(0, 0, rounds),
         }
     }
 
@@ -186,63 +53,81 @@ impl StableHasher {
     }
 }
 
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StableHasher {
     #[inline]
     pub fn finalize(self) -> (u64, u64) {
         self.state.finish128()
     }
-}
 
-impl Hasher for StableHasher {
-    fn finish(&self) -> u64 {
-        panic!("use StableHasher::finalize instead");
+    /// `Hasher::write_str` of rustc's hasher, which is not stable yet.
+    /// The default `Hasher::write_str` used by `Hash for str` writes the
+    /// same bytes.
+    #[inline]
+    pub fn write_str(&mut self, s: &str) {
+        self.state.write_str(s);
     }
 
+    /// `Hasher::write_length_prefix`, which is not stable yet.
     #[inline]
-    fn write(&mut self, bytes: &[u8]) {
-        if self.debug { dbg!("hash write: {:?}", bytes); }
-        self.state.write(bytes);
+    pub fn write_length_prefix(&mut self, len: usize) {
+        // Our impl for `usize` will extend it if needed.
+        self.write_usize(len);
     }
+}
 
+impl StableHasherResult for u128 {
     #[inline]
-    fn write_str(&mut self, s: &str) {
-        if self.debug { dbg!("hash write_str: {:?}", s); }
-        self.state.write_str(s);
+    fn finish(hasher: StableHasher) -> Self {
+        let (_0, _1) = hasher.finalize();
+        u128::from(_0) | (u128::from(_1) << 64)
     }
+}
 
+impl StableHasherResult for u64 {
     #[inline]
-    fn write_length_prefix(&mut self, len: usize) {
-        // Our impl for `usize` will extend it if needed.
-        self.write_usize(len);
+    fn finish(hasher: StableHasher) -> Self {
+        hasher.finalize().0
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        panic!("use StableHasher::finalize instead");
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.state.write(bytes);
     }
 
     #[inline]
     fn write_u8(&mut self, i: u8) {
-        if self.debug { dbg!("hash write_u8: {:?}", i); }
         self.state.write_u8(i);
     }
 
     #[inline]
     fn write_u16(&mut self, i: u16) {
-        if self.debug { dbg!("hash write_u16: {:?}", i); }
         self.state.short_write(i.to_le_bytes());
     }
 
     #[inline]
     fn write_u32(&mut self, i: u32) {
-        if self.debug { dbg!("hash write_u32: {:?}", i); }
         self.state.short_write(i.to_le_bytes());
     }
 
     #[inline]
     fn write_u64(&mut self, i: u64) {
-        if self.debug { dbg!("hash write_u64: {:?}", i); }
         self.state.short_write(i.to_le_bytes());
     }
 
     #[inline]
     fn write_u128(&mut self, i: u128) {
-        if self.debug { dbg!("hash write_u128: {:?}", i); }
         self.write_u64(i as u64);
         self.write_u64((i >> 64) as u64);
     }
@@ -252,43 +137,36 @@ impl Hasher for StableHasher {
         // Always treat usize as u64 so we get the same results on 32 and 64 bit
         // platforms. This is important for symbol hashes when cross compiling,
         // for example.
-        if self.debug { dbg!("hash write_usize: {:?}", i); }
         self.state.short_write((i as u64).to_le_bytes());
     }
 
     #[inline]
     fn write_i8(&mut self, i: i8) {
-        if self.debug { dbg!("hash write_i8: {:?}", i); }
         self.state.write_i8(i);
     }
 
     #[inline]
     fn write_i16(&mut self, i: i16) {
-        if self.debug { dbg!("hash write_i16: {:?}", i); }
         self.state.short_write((i as u16).to_le_bytes());
     }
 
     #[inline]
     fn write_i32(&mut self, i: i32) {
-        if self.debug { dbg!("hash write_i32: {:?}", i); }
         self.state.short_write((i as u32).to_le_bytes());
     }
 
     #[inline]
     fn write_i64(&mut self, i: i64) {
-        if self.debug { dbg!("hash write_i64: {:?}", i); }
         self.state.short_write((i as u64).to_le_bytes());
     }
 
     #[inline]
     fn write_i128(&mut self, i: i128) {
-        if self.debug { dbg!("hash write_i128: {:?}", i); }
         self.state.write(&(i as u128).to_le_bytes());
     }
 
     #[inline]
     fn write_isize(&mut self, i: isize) {
-        if self.debug { dbg!("hash write_isize: {:?}", i); }
         // Always treat isize as a 64-bit number so we get the same results on 32 and 64 bit
         // platforms. This is important for symbol hashes when cross compiling,
         // for example. Sign extending here is preferable as it means that the
@@ -345,8 +223,8 @@ impl Hasher for StableHasher {
 ///   past.
 ///
 /// - `hash_stable()` must be independent of the current
-///    compilation session. E.g. they must not hash memory addresses or other
-///    things that are "randomly" assigned per compilation session.
+///   compilation session. E.g. they must not hash memory addresses or other
+///   things that are "randomly" assigned per compilation session.
 ///
 /// - `hash_stable()` must be independent of the host architecture. The
 ///   `StableHasher` takes care of endianness and `isize`/`usize` platform
@@ -392,6 +270,11 @@ pub trait ToStableHashKey<HCX> {
 /// The associated constant `CAN_USE_UNSTABLE_SORT` denotes whether
 /// unstable sorting can be used for this type. Set to true if and
 /// only if `a == b` implies `a` and `b` are fully indistinguishable.
+///
+/// # Safety
+///
+/// Only implement this for types whose order really is stable as described
+/// above, sorted collections rely on it when they are hashed.
 pub unsafe trait StableOrd: Ord {
     const CAN_USE_UNSTABLE_SORT: bool;
 }
@@ -411,14 +294,14 @@ unsafe impl<T: StableOrd> StableOrd for &T {
 /// Use `#[derive(HashStable_Generic)]` instead.
 macro_rules! impl_stable_traits_for_trivial_type {
     ($t:ty) => {
-        impl<CTX> $crate::stable_hasher::HashStable<CTX> for $t {
+        impl<CTX> $crate::rustc::HashStable<CTX> for $t {
             #[inline]
-            fn hash_stable(&self, _: &mut CTX, hasher: &mut $crate::stable_hasher::StableHasher) {
+            fn hash_stable(&self, _: &mut CTX, hasher: &mut $crate::rustc::StableHasher) {
                 ::std::hash::Hash::hash(self, hasher);
             }
         }
 
-        unsafe impl $crate::stable_hasher::StableOrd for $t {
+        unsafe impl $crate::rustc::StableOrd for $t {
             const CAN_USE_UNSTABLE_SORT: bool = true;
         }
     };
@@ -445,12 +328,6 @@ impl_stable_traits_for_trivial_type!(());
 impl_stable_traits_for_trivial_type!(Hash64);
 impl_stable_traits_for_trivial_type!(Hash128);
 
-// impl<CTX> HashStable<CTX> for ! {
-//     fn hash_stable(&self, _ctx: &mut CTX, _hasher: &mut StableHasher) {
-//         unreachable!()
-//     }
-// }
-
 impl<CTX, T> HashStable<CTX> for PhantomData<T> {
     fn hash_stable(&self, _ctx: &mut CTX, _hasher: &mut StableHasher) {}
 }
@@ -555,7 +432,7 @@ unsafe impl<T1: StableOrd, T2: StableOrd, T3: StableOrd, T4: StableOrd> StableOr
 }
 
 impl<T: HashStable<CTX>, CTX> HashStable<CTX> for [T] {
-    default fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
+    fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
         self.len().hash_stable(ctx, hasher);
         for item in self {
             item.hash_stable(ctx, hasher);
@@ -563,13 +440,6 @@ impl<T: HashStable<CTX>, CTX> HashStable<CTX> for [T] {
     }
 }
 
-impl<CTX> HashStable<CTX> for [u8] {
-    fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
-        self.len().hash_stable(ctx, hasher);
-        hasher.write(self);
-    }
-}
-
 impl<T: HashStable<CTX>, CTX> HashStable<CTX> for Vec<T> {
     #[inline]
     fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
@@ -577,45 +447,6 @@ impl<T: HashStable<CTX>, CTX> HashStable<CTX> for Vec<T> {
     }
 }
 
-// impl<K, V, R, CTX> HashStable<CTX> for indexmap::IndexMap<K, V, R>
-// where
-//     K: HashStable<CTX> + Eq + Hash,
-//     V: HashStable<CTX>,
-//     R: BuildHasher,
-// {
-//     #[inline]
-//     fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
-//         self.len().hash_stable(ctx, hasher);
-//         for kv in self {
-//             kv.hash_stable(ctx, hasher);
-//         }
-//     }
-// }
-
-// impl<K, R, CTX> HashStable<CTX> for indexmap::IndexSet<K, R>
-// where
-//     K: HashStable<CTX> + Eq + Hash,
-//     R: BuildHasher,
-// {
-//     #[inline]
-//     fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
-//         self.len().hash_stable(ctx, hasher);
-//         for key in self {
-//             key.hash_stable(ctx, hasher);
-//         }
-//     }
-// }
-
-// impl<A, const N: usize, CTX> HashStable<CTX> for SmallVec<[A; N]>
-// where
-//     A: HashStable<CTX>,
-// {
-//     #[inline]
-//     fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
-//         self[..].hash_stable(ctx, hasher);
-//     }
-// }
-
 impl<T: ?Sized + HashStable<CTX>, CTX> HashStable<CTX> for Box<T> {
     #[inline]
     fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
@@ -673,7 +504,10 @@ impl<HCX, T1: ToStableHashKey<HCX>, T2: ToStableHashKey<HCX>> ToStableHashKey<HC
     type KeyType = (T1::KeyType, T2::KeyType);
     #[inline]
     fn to_stable_hash_key(&self, hcx: &HCX) -> Self::KeyType {
-        (self.0.to_stable_hash_key(hcx), self.1.to_stable_hash_key(hcx))
+        (
+            self.0.to_stable_hash_key(hcx),
+            self.1.to_stable_hash_key(hcx),
+        )
     }
 }
 
@@ -724,7 +558,7 @@ where
     }
 }
 
-impl<'a, T, CTX> HashStable<CTX> for &'a T
+impl<T, CTX> HashStable<CTX> for &T
 where
     T: HashStable<CTX> + ?Sized,
 {
@@ -752,39 +586,6 @@ where
     }
 }
 
-// impl<I: Idx, T, CTX> HashStable<CTX> for IndexVec<I, T>
-// where
-//     T: HashStable<CTX>,
-// {
-//     fn hash_stable(&self, ctx: &mut CTX, hasher: &mut StableHasher) {
-//         self.len().hash_stable(ctx, hasher);
-//         for v in &self.raw {
-//             v.hash_stable(ctx, hasher);
-//         }
-//     }
-// }
-
-// impl<I: Idx, CTX> HashStable<CTX> for BitSet<I> {
-//     fn hash_stable(&self, _ctx: &mut CTX, hasher: &mut StableHasher) {
-//         ::std::hash::Hash::hash(self, hasher);
-//     }
-// }
-
-// impl<R: Idx, C: Idx, CTX> HashStable<CTX> for bit_set::BitMatrix<R, C> {
-//     fn hash_stable(&self, _ctx: &mut CTX, hasher: &mut StableHasher) {
-//         ::std::hash::Hash::hash(self, hasher);
-//     }
-// }
-
-// impl<T, CTX> HashStable<CTX> for bit_set::FiniteBitSet<T>
-// where
-//     T: HashStable<CTX> + bit_set::FiniteBitSetTy,
-// {
-//     fn hash_stable(&self, hcx: &mut CTX, hasher: &mut StableHasher) {
-//         self.0.hash_stable(hcx, hasher);
-//     }
-// }
-
 impl_stable_traits_for_trivial_type!(::std::path::Path);
 impl_stable_traits_for_trivial_type!(::std::path::PathBuf);
 
@@ -796,18 +597,23 @@ where
 {
     #[inline]
     fn hash_stable(&self, hcx: &mut HCX, hasher: &mut StableHasher) {
-        stable_hash_reduce(hcx, hasher, self.iter(), self.len(), |hasher, hcx, (key, value)| {
-            let key = key.to_stable_hash_key(hcx);
-            key.hash_stable(hcx, hasher);
-            value.hash_stable(hcx, hasher);
-        });
+        stable_hash_reduce(
+            hcx,
+            hasher,
+            self.iter(),
+            self.len(),
+            |hasher, hcx, (key, value)| {
+                let key = key.to_stable_hash_key(hcx);
+                key.hash_stable(hcx, hasher);
+                value.hash_stable(hcx, hasher);
+            },
+        );
     }
 }
 
 // It is not safe to implement HashStable for HashSet or any other collection type
 // with unstable but observable iteration order.
 // See https://github.com/rust-lang/compiler-team/issues/533 for further information.
-// impl<V, HCX> !HashStable<HCX> for std::collections::HashSet<V> {}
 
 impl<K, V, HCX> HashStable<HCX> for ::std::collections::BTreeMap<K, V>
 where
@@ -874,3 +680,55 @@ fn stable_hash_reduce<HCX, I, C, F>(
 pub struct HashingControls {
     pub hash_spans: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{HashStable, HashingControls, SipRounds, StableHasher};
+    use std::hash::Hasher;
+
+    // The expected values are from rustc's implementation, on nightly.
+
+    #[test]
+    fn integers() {
+        let mut hasher = StableHasher::new();
+        hasher.write_str("playground");
+        hasher.write_isize(5);
+        hasher.write_isize(300);
+        hasher.write_isize(-1);
+        hasher.write_u32(7);
+        hasher.write_u128(1 << 100 | 3);
+        assert_eq!(hasher.finalize(), (0x724c3fe8f66ba719, 0x32c18f624bcba198));
+
+        let mut hasher = StableHasher::new_with_rounds(SipRounds::Sip24);
+        hasher.write_str("playground");
+        hasher.write_isize(5);
+        hasher.write_isize(300);
+        hasher.write_isize(-1);
+        hasher.write_u32(7);
+        hasher.write_u128(1 << 100 | 3);
+        assert_eq!(hasher.finalize(), (0xd2b7cdad374d5649, 0xcf8c5163919059d2));
+    }
+
+    #[test]
+    fn hash_stable() {
+        let mut hcx = HashingControls { hash_spans: false };
+        let value = (
+            "foo",
+            vec![1_u8, 2, 3],
+            String::from("bar"),
+            (vec![1_u16, 2], Some(5_u8), true),
+        );
+        let mut hasher = StableHasher::new();
+        value.hash_stable(&mut hcx, &mut hasher);
+        assert_eq!(hasher.finalize(), (0x681ff0c15c539be4, 0xfcacb3b656661924));
+
+        // Without specialization byte slices are hashed one byte at a time,
+        // which must not change the hash.
+        let mut hasher = StableHasher::new();
+        b"bytes"[..].hash_stable(&mut hcx, &mut hasher);
+        let mut expected = StableHasher::new();
+        expected.write_usize(5);
+        expected.write(b"bytes");
+        assert_eq!(hasher.finalize(), expected.finalize());
+    }
+}
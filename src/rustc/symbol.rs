@@ -0,0 +1,161 @@
+//! Collisions of legacy symbol hashes.
+//!
+//! The legacy mangling ends every symbol with `17h<hash>E`, a stable hash of
+//! the item's `DefPathHash`, its type and its signature.  Two functions with
+//! the same signature only differ by their `DefPathHash`, so the free bits
+//! live in their names.
+
+use super::def_path::{DefPath, DefPathData, DefPathHash};
+use crate::adapters::{low_mask, truncated_point_methods, TruncatedPoint};
+use crate::error::{Error, Result};
+use crate::{HashAdapter, Reporter};
+use core::ops::ControlFlow;
+
+/// Collides the legacy symbol hashes of two functions `fn foo_00c0ffee()`
+/// and `fn bar_1234()` under the same parent, named by a prefix and the
+/// point in hex.
+///
+/// Only the lowest `bits` bits of the hash are compared.  The symbols only
+/// exist when the functions are code generated, like public functions of a
+/// library.
+pub struct SymbolHashAdapter {
+    parent: DefPathHash,
+    /// The crate name and the parent segments, the start of the symbols.
+    path: Vec<String>,
+    prefixes: [String; 2],
+    point: TruncatedPoint,
+    reporter: Reporter,
+}
+
+impl SymbolHashAdapter {
+    /// Collide functions inside `parent` of the crate `crate_name`, failing
+    /// if its profile does not model symbol hashes or a parent segment has
+    /// no name.
+    pub fn new(crate_name: &str, parent: &DefPath, prefix_a: &str, prefix_b: &str) -> Result<Self> {
+        let profile = parent.crate_id.profile;
+        if profile.symbol_layout.is_none() {
+            return Err(Error::Invalid(format!(
+                "profile {} does not model symbol hashes",
+                profile.name
+            )));
+        }
+        let mut path = vec![crate_name.to_owned()];
+        for segment in &parent.data {
+            match segment.data.name() {
+                Some(name) => path.push(name.to_owned()),
+                None => {
+                    return Err(Error::Invalid(format!(
+                        "symbols are only modelled for paths of named items, not {}",
+                        segment
+                    )))
+                }
+            }
+        }
+        Ok(Self {
+            parent: parent.hash(),
+            path,
+            prefixes: [prefix_a.to_owned(), prefix_b.to_owned()],
+            point: TruncatedPoint::new(64, low_mask(18)),
+            reporter: Reporter::default(),
+        })
+    }
+
+    /// Compare only the lowest `bits` bits, with points distinguished by
+    /// `trail_mask`.
+    pub fn with_bits(mut self, bits: u32, trail_mask: u64) -> Self {
+        self.point = TruncatedPoint::new(bits, trail_mask);
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// The name of the function for point `x`, the first one when `bi` is
+    /// set.
+    pub fn name(&self, x: u64, bi: bool) -> String {
+        self.point.hex_name(&self.prefixes[!bi as usize], x)
+    }
+
+    /// The full symbol hash of the function for point `x`.
+    pub fn symbol_hash(&self, x: u64, bi: bool) -> u64 {
+        let item = self
+            .parent
+            .child(&DefPathData::ValueNs(self.name(x, bi)), 0);
+        let profile = self.parent.crate_id.profile;
+        profile.fn_symbol_hash(item).expect("checked by new")
+    }
+
+    /// The mangled symbol of the function for point `x`.
+    pub fn symbol(&self, x: u64, bi: bool) -> String {
+        let mut symbol = "_ZN".to_owned();
+        for name in self.path.iter().chain([&self.name(x, bi)]) {
+            symbol += &format!("{}{}", name.len(), name);
+        }
+        symbol + &format!("17h{:016x}E", self.symbol_hash(x, bi))
+    }
+}
+
+impl HashAdapter for SymbolHashAdapter {
+    type Point = u64;
+
+    truncated_point_methods!();
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.point.truncate(self.symbol_hash(x, bi))
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        assert_eq!(
+            self.point.truncate(self.symbol_hash(a, true)),
+            self.point.truncate(self.symbol_hash(b, false))
+        );
+        self.reporter.report(
+            &a,
+            &b,
+            format_args!(
+                "found collision! (a, fn {}()) (b, fn {}()) {} {}",
+                self.name(a, true),
+                self.name(b, false),
+                self.symbol(a, true),
+                self.symbol(b, false)
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SymbolHashAdapter;
+    use crate::rustc::{DefPath, RUSTC_1_72, RUSTC_1_95};
+    use crate::HashAdapter;
+
+    #[test]
+    fn symbols() {
+        // The library of `profile::tests::rustc_1_95`.
+        let crate_id = RUSTC_1_95.crate_id("symtest", false, vec!["051dac071847dbb3".to_owned()]);
+        let root = RUSTC_1_95.snippet_root(crate_id);
+        let adapter = SymbolHashAdapter::new("symtest", &root, "foo_", "bar_")
+            .unwrap()
+            .with_bits(32, 0xff);
+        assert_eq!(
+            adapter.symbol(0xc0ffee, false),
+            "_ZN7symtest12bar_00c0ffee17h0cf4f5b158d2bce8E"
+        );
+        assert_eq!(adapter.next_point(0xc0ffee, false), 0x58d2bce8);
+
+        let inner = DefPath::parse(crate_id, "mod inner").unwrap();
+        let adapter = SymbolHashAdapter::new("symtest", &inner, "foo_", "bar_").unwrap();
+        assert!(adapter
+            .symbol(1, true)
+            .starts_with("_ZN7symtest5inner20foo_0000000000000001"));
+
+        let impl_path = DefPath::parse(crate_id, "impl").unwrap();
+        assert!(SymbolHashAdapter::new("symtest", &impl_path, "foo_", "bar_").is_err());
+        let crate_id = RUSTC_1_72.crate_id("symtest", false, vec![]);
+        let root = RUSTC_1_72.snippet_root(crate_id);
+        assert!(SymbolHashAdapter::new("symtest", &root, "foo_", "bar_").is_err());
+    }
+}
@@ -0,0 +1,472 @@
+//! `TypeId` collisions between structs.
+//!
+//! The `TypeId` of a struct hashes its `AdtDef`: the names of the struct and
+//! of its fields, but not the types of the fields.  The free bits live
+//! either in the name of a field, `struct Foo { pub x00c0ffee: usize }`, or
+//! in the name of the type argument of a generic struct: `Foo<A00c0ffee>`
+//! and `Bar<B1234>` collide when the stable hashes of the whole types do,
+//! with `Foo` and `Bar` kept as they are.  Only compilers whose ADT layout
+//! is modelled by their `Profile` are supported.
+
+use super::def_path::{DefPath, DefPathHash, DisambiguatedDefPathData};
+use super::proof::{ProofCrate, Proofs};
+use crate::adapters::{low_mask, truncated_point_methods, TruncatedPoint};
+use crate::error::{Error, Result};
+use crate::{HashAdapter, Reporter};
+use core::ops::ControlFlow;
+use std::path::PathBuf;
+
+/// The field of the generic structs, `struct Foo<T> { pub value: T }`.
+const FIELD: &str = "value";
+
+/// Collides the `TypeId`s of two structs with a single field named by a
+/// prefix and the point in hex, like `struct Foo { pub x00c0ffee: usize }`
+/// and `struct Bar { pub y1234: usize }`.
+///
+/// Both structs are declared under the same parent, so they need different
+/// names.  Only the lowest `bits` bits of the `TypeId` are compared.
+pub struct StructTypeIdAdapter {
+    parent: DefPathHash,
+    /// The struct name and the field prefix of both types.
+    types: [(String, String); 2],
+    point: TruncatedPoint,
+    reporter: Reporter,
+    proofs: Proofs,
+}
+
+impl StructTypeIdAdapter {
+    /// Collide `type_a.0 { type_a.1.. }` with `type_b.0 { type_b.1.. }`,
+    /// failing if the profile of `parent` does not model ADT hashes or the
+    /// structs have the same name.
+    pub fn new(parent: &DefPath, type_a: (&str, &str), type_b: (&str, &str)) -> Result<Self> {
+        let profile = parent.crate_id.profile;
+        if profile.adt_layout.is_none() {
+            return Err(Error::Invalid(format!(
+                "profile {} does not model the TypeIds of structs",
+                profile.name
+            )));
+        }
+        if type_a.0 == type_b.0 {
+            return Err(Error::Invalid(format!(
+                "both structs are named {}",
+                type_a.0
+            )));
+        }
+        Ok(Self {
+            parent: parent.hash(),
+            types: [
+                (type_a.0.to_owned(), type_a.1.to_owned()),
+                (type_b.0.to_owned(), type_b.1.to_owned()),
+            ],
+            point: TruncatedPoint::new(64, low_mask(18)),
+            reporter: Reporter::default(),
+            proofs: Proofs::default(),
+        })
+    }
+
+    /// Compare only the lowest `bits` bits, with points distinguished by
+    /// `trail_mask`.
+    pub fn with_bits(mut self, bits: u32, trail_mask: u64) -> Self {
+        self.point = TruncatedPoint::new(bits, trail_mask);
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Write a `ProofCrate` for every collision of the full `TypeId` into a
+    /// directory of `dir`, like `DefPathAdapter::with_proof_dir`.  `parent`
+    /// is the path from the root of the proof's snippet to the items, and
+    /// must be made of modules.
+    pub fn with_proof_dir<P: Into<PathBuf>>(
+        mut self,
+        dir: P,
+        proof: ProofCrate,
+        parent: &[DisambiguatedDefPathData],
+    ) -> Result<Self> {
+        self.proofs.set_dir(dir.into(), proof, parent)?;
+        Ok(self)
+    }
+
+    /// Build and run every proof with the local compiler, and add the
+    /// outcome to the report.
+    pub fn with_proof_verification(mut self) -> Self {
+        self.proofs.set_verify();
+        self
+    }
+
+    /// The name of the field for point `x`, of the first struct when `bi`
+    /// is set.
+    pub fn field_name(&self, x: u64, bi: bool) -> String {
+        let (_, prefix) = &self.types[!bi as usize];
+        self.point.hex_name(prefix, x)
+    }
+
+    /// The declaration of the struct for point `x`, like
+    /// `struct Foo { pub x00c0ffee: usize }`.
+    pub fn declaration(&self, x: u64, bi: bool) -> String {
+        format!(
+            "struct {} {{ pub {}: usize }}",
+            self.types[!bi as usize].0,
+            self.field_name(x, bi)
+        )
+    }
+
+    /// The full `TypeId` of the struct for point `x`.
+    pub fn type_id(&self, x: u64, bi: bool) -> u128 {
+        let profile = self.parent.crate_id.profile;
+        let field = self.field_name(x, bi);
+        profile
+            .struct_type_id(self.parent, &self.types[!bi as usize].0, &[&field])
+            .expect("checked by new")
+    }
+
+    /// The proof that the structs for `a` and `b` collide.
+    pub fn proof(&self, a: u64, b: u64) -> Option<ProofCrate> {
+        let proof = self.proofs.template()?;
+        let items = [(a, true), (b, false)]
+            .iter()
+            .map(|&(x, bi)| {
+                format!(
+                    "#[allow(dead_code)]\npub struct {} {{\n    pub {}: usize,\n}}\n",
+                    self.types[!bi as usize].0,
+                    self.field_name(x, bi)
+                )
+            })
+            .collect::<String>();
+        let prefix = self.proofs.prefix();
+        let note = format!(
+            "`{}` and `{}` have the same TypeId {:016x}.\n\nBuild with ./build.sh, it passes -C metadata={}.",
+            self.declaration(a, true),
+            self.declaration(b, false),
+            self.type_id(a, true),
+            proof.metadata.join(",")
+        );
+        Some(
+            proof
+                .clone()
+                .with_items(
+                    &self.proofs.in_modules(items.trim_end()),
+                    &format!("{}{}", prefix, self.types[0].0),
+                    &format!("{}{}", prefix, self.types[1].0),
+                    &note,
+                )
+                .with_type_id(
+                    self.type_id(a, true),
+                    self.parent.crate_id.profile.type_id_bits,
+                ),
+        )
+    }
+}
+
+impl HashAdapter for StructTypeIdAdapter {
+    type Point = u64;
+
+    truncated_point_methods!();
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.point.truncate(self.type_id(x, bi) as u64)
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        let type_id = self.type_id(a, true);
+        let other = self.type_id(b, false);
+        assert_eq!(
+            self.point.truncate(type_id as u64),
+            self.point.truncate(other as u64)
+        );
+
+        let files = match type_id == other {
+            true => self
+                .proofs
+                .write_for_report(type_id as u64, self.parent.crate_id, || self.proof(a, b)),
+            false => String::new(),
+        };
+        self.reporter.report(
+            &a,
+            &b,
+            format_args!(
+                "found collision! (a, {}) (b, {}) type_id={:016x}{}",
+                self.declaration(a, true),
+                self.declaration(b, false),
+                type_id,
+                files
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+}
+
+/// Collides the `TypeId`s of two generic structs instantiated with a struct
+/// named by a prefix and the point in hex, like `Foo<A00c0ffee>` and
+/// `Bar<B1234>`.
+///
+/// All items are declared under the same parent: the generic structs as
+/// `struct Foo<T> { pub value: T }` and the arguments as
+/// `struct A00c0ffee {}`.
+/// Only the lowest `bits` bits of the `TypeId` are compared.
+pub struct GenericTypeIdAdapter {
+    parent: DefPathHash,
+    /// The generic struct and the argument prefix of both types.
+    types: [(String, String); 2],
+    point: TruncatedPoint,
+    reporter: Reporter,
+    proofs: Proofs,
+}
+
+impl GenericTypeIdAdapter {
+    /// Collide `type_a.0<type_a.1..>` with `type_b.0<type_b.1..>`, failing if
+    /// the profile of `parent` does not model the ADT hashes of generic
+    /// structs and their arguments.
+    pub fn new(parent: &DefPath, type_a: (&str, &str), type_b: (&str, &str)) -> Result<Self> {
+        let profile = parent.crate_id.profile;
+        let hash = parent.hash();
+        let arg = profile.struct_ty_hash(hash, type_a.1, &[], &[]);
+        if arg
+            .and_then(|arg| profile.struct_ty_hash(hash, type_a.0, &[FIELD], &[arg]))
+            .is_none()
+        {
+            return Err(Error::Invalid(format!(
+                "profile {} does not model the TypeIds of generic structs",
+                profile.name
+            )));
+        }
+        Ok(Self {
+            parent: parent.hash(),
+            types: [
+                (type_a.0.to_owned(), type_a.1.to_owned()),
+                (type_b.0.to_owned(), type_b.1.to_owned()),
+            ],
+            point: TruncatedPoint::new(64, low_mask(18)),
+            reporter: Reporter::default(),
+            proofs: Proofs::default(),
+        })
+    }
+
+    /// Compare only the lowest `bits` bits, with points distinguished by
+    /// `trail_mask`.
+    pub fn with_bits(mut self, bits: u32, trail_mask: u64) -> Self {
+        self.point = TruncatedPoint::new(bits, trail_mask);
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Write a `ProofCrate` for every collision of the full `TypeId` into a
+    /// directory of `dir`, like `DefPathAdapter::with_proof_dir`.  `parent`
+    /// is the path from the root of the proof's snippet to the items, and
+    /// must be made of modules.
+    pub fn with_proof_dir<P: Into<PathBuf>>(
+        mut self,
+        dir: P,
+        proof: ProofCrate,
+        parent: &[DisambiguatedDefPathData],
+    ) -> Result<Self> {
+        self.proofs.set_dir(dir.into(), proof, parent)?;
+        Ok(self)
+    }
+
+    /// Build and run every proof with the local compiler, and add the
+    /// outcome to the report.
+    pub fn with_proof_verification(mut self) -> Self {
+        self.proofs.set_verify();
+        self
+    }
+
+    /// The name of the type argument for point `x`, of the first type when
+    /// `bi` is set.
+    pub fn arg_name(&self, x: u64, bi: bool) -> String {
+        let (_, prefix) = &self.types[!bi as usize];
+        self.point.hex_name(prefix, x)
+    }
+
+    /// The instantiation for point `x`, like `Foo<A00c0ffee>`.
+    pub fn type_name(&self, x: u64, bi: bool) -> String {
+        format!("{}<{}>", self.types[!bi as usize].0, self.arg_name(x, bi))
+    }
+
+    /// The full `TypeId` of the instantiation for point `x`.
+    pub fn type_id(&self, x: u64, bi: bool) -> u128 {
+        let profile = self.parent.crate_id.profile;
+        let arg = profile.struct_ty_hash(self.parent, &self.arg_name(x, bi), &[], &[]);
+        let ty = profile.struct_ty_hash(
+            self.parent,
+            &self.types[!bi as usize].0,
+            &[FIELD],
+            &[arg.expect("checked by new")],
+        );
+        profile.type_id(self.parent.crate_id, ty.expect("checked by new"))
+    }
+
+    /// The proof that the instantiations for `a` and `b` collide.
+    pub fn proof(&self, a: u64, b: u64) -> Option<ProofCrate> {
+        let proof = self.proofs.template()?;
+        let mut items = String::new();
+        for (i, (generic, _)) in self.types.iter().enumerate() {
+            if i == 0 || *generic != self.types[0].0 {
+                items += &format!(
+                    "#[allow(dead_code)]\npub struct {}<T> {{\n    pub {}: T,\n}}\n",
+                    generic, FIELD
+                );
+            }
+        }
+        items += &format!(
+            "pub struct {} {{}}\npub struct {} {{}}",
+            self.arg_name(a, true),
+            self.arg_name(b, false)
+        );
+        let prefix = self.proofs.prefix();
+        let path = |x, bi: bool| {
+            let (generic, _) = &self.types[!bi as usize];
+            format!("{0}{1}<{0}{2}>", prefix, generic, self.arg_name(x, bi))
+        };
+        let note = format!(
+            "`{}` and `{}` have the same TypeId {:016x}.\n\nBuild with ./build.sh, it passes -C metadata={}.",
+            self.type_name(a, true),
+            self.type_name(b, false),
+            self.type_id(a, true),
+            proof.metadata.join(",")
+        );
+        Some(
+            proof
+                .clone()
+                .with_items(
+                    &self.proofs.in_modules(&items),
+                    &path(a, true),
+                    &path(b, false),
+                    &note,
+                )
+                .with_type_id(
+                    self.type_id(a, true),
+                    self.parent.crate_id.profile.type_id_bits,
+                ),
+        )
+    }
+}
+
+impl HashAdapter for GenericTypeIdAdapter {
+    type Point = u64;
+
+    truncated_point_methods!();
+
+    fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+        self.point.truncate(self.type_id(x, bi) as u64)
+    }
+
+    fn report_collision(&self, a: Self::Point, b: Self::Point) -> ControlFlow<(), ()> {
+        let type_id = self.type_id(a, true);
+        let other = self.type_id(b, false);
+        assert_eq!(
+            self.point.truncate(type_id as u64),
+            self.point.truncate(other as u64)
+        );
+
+        let files = match type_id == other {
+            true => self
+                .proofs
+                .write_for_report(type_id as u64, self.parent.crate_id, || self.proof(a, b)),
+            false => String::new(),
+        };
+        self.reporter.report(
+            &a,
+            &b,
+            format_args!(
+                "found collision! (a, {}) (b, {}) type_id={:016x}{}",
+                self.type_name(a, true),
+                self.type_name(b, false),
+                type_id,
+                files
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GenericTypeIdAdapter, StructTypeIdAdapter};
+    use crate::rustc::{DefPath, ProofCrate, RUSTC_1_64, RUSTC_1_72, RUSTC_1_95};
+    use crate::HashAdapter;
+
+    #[test]
+    fn instantiations() {
+        // `TypeId::of::<Foo<A0000c0ffee>>()` of a binary built by rustc 1.95.0
+        // with `--crate-name playground -C metadata=051dac071847dbb3`, from
+        // the items of the proof below.
+        let crate_id = RUSTC_1_95.crate_id("playground", true, vec!["051dac071847dbb3".to_owned()]);
+        let root = RUSTC_1_95.snippet_root(crate_id);
+        let adapter = GenericTypeIdAdapter::new(&root, ("Foo", "A"), ("Bar", "B"))
+            .unwrap()
+            .with_bits(40, 0xff);
+        assert_eq!(adapter.type_name(0xc0ffee, true), "Foo<A0000c0ffee>");
+        assert_eq!(
+            adapter.type_id(0xc0ffee, true),
+            0xfb56704b264f45d5907b0f3fbf5c8fa0
+        );
+        assert_eq!(adapter.next_point(0xc0ffee, false), 0x3659dde95c);
+
+        let proof = ProofCrate::new("playground", vec!["051dac071847dbb3".to_owned()], false);
+        let proof = adapter
+            .with_proof_dir("proofs", proof, &[])
+            .unwrap()
+            .proof(0xc0ffee, 0xc0ffee)
+            .unwrap();
+        assert_eq!(proof.types, ["Foo<A0000c0ffee>", "Bar<B0000c0ffee>"]);
+        assert_eq!(
+            proof.items,
+            "#[allow(dead_code)]\npub struct Foo<T> {\n    pub value: T,\n}\n\
+             #[allow(dead_code)]\npub struct Bar<T> {\n    pub value: T,\n}\n\
+             pub struct A0000c0ffee {}\npub struct B0000c0ffee {}"
+        );
+
+        for profile in [&RUSTC_1_64, &RUSTC_1_72] {
+            let root = profile.snippet_root(profile.crate_id("playground", true, vec![]));
+            assert!(GenericTypeIdAdapter::new(&root, ("Foo", "A"), ("Bar", "B")).is_err());
+        }
+    }
+
+    #[test]
+    fn struct_fields() {
+        // The struct of `profile::tests::rustc_1_64`.
+        let crate_id = RUSTC_1_64.crate_id("playground", true, vec!["a0ecb98bfb1b38c8".to_owned()]);
+        let main = RUSTC_1_64.snippet_root(crate_id);
+        let adapter = StructTypeIdAdapter::new(&main, ("Foo", "x"), ("Bar", "y")).unwrap();
+        assert_eq!(
+            adapter.declaration(0xc0ffee, true),
+            "struct Foo { pub x0000000000c0ffee: usize }"
+        );
+        assert_eq!(adapter.next_point(0xc0ffee, true), 0x433f9d5605662ad5);
+
+        let proof = ProofCrate::new("playground", vec!["a0ecb98bfb1b38c8".to_owned()], true);
+        let inner = DefPath::parse(crate_id, "mod inner").unwrap().data;
+        let proof = adapter
+            .with_bits(40, 0xff)
+            .with_proof_dir("proofs", proof, &inner)
+            .unwrap()
+            .proof(1, 2)
+            .unwrap();
+        assert_eq!(proof.types, ["inner::Foo", "inner::Bar"]);
+        assert!(proof
+            .items
+            .contains("    pub struct Bar {\n        pub y0000000002: usize,\n    }\n}"));
+
+        assert!(StructTypeIdAdapter::new(&main, ("Foo", "x"), ("Foo", "y")).is_err());
+
+        // The struct of `profile::tests::rustc_1_95_type_ids`.
+        let crate_id = RUSTC_1_95.crate_id("playground", true, vec!["051dac071847dbb3".to_owned()]);
+        let root = RUSTC_1_95.snippet_root(crate_id);
+        let adapter = StructTypeIdAdapter::new(&root, ("Foo", "x"), ("Bar", "y")).unwrap();
+        assert_eq!(
+            adapter.type_id(0xc0ffee, true),
+            0x0a570fe8dab1346e8577fe778e34a3f5
+        );
+        let crate_id = RUSTC_1_72.crate_id("playground", true, vec![]);
+        let root = RUSTC_1_72.snippet_root(crate_id);
+        assert!(StructTypeIdAdapter::new(&root, ("Foo", "x"), ("Bar", "y")).is_err());
+    }
+}
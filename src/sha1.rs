@@ -0,0 +1,96 @@
+//! The SHA-1 compression function.
+
+#![allow(clippy::many_single_char_names)]
+
+use crate::block_hash::{be_words, write_words, BlockHash, BLOCK_SIZE};
+
+/// Round constants, one per 20 rounds
+pub const RC: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// Init state
+pub const S0: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+#[inline]
+pub fn compress(state: &mut [u32; 5], input: &[u32; 16]) {
+    let mut w = [0_u32; 80];
+    w[..16].copy_from_slice(input);
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, wi) in w.into_iter().enumerate() {
+        let f = match i / 20 {
+            0 => (b & c) | (!b & d),
+            2 => (b & c) | (b & d) | (c & d),
+            _ => b ^ c ^ d,
+        };
+        let t = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(RC[i / 20])
+            .wrapping_add(wi);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = t;
+    }
+
+    for (s, x) in state.iter_mut().zip([a, b, c, d, e]) {
+        *s = s.wrapping_add(x);
+    }
+}
+
+/// SHA-1 as a `BlockHash`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha1;
+
+impl BlockHash for Sha1 {
+    const NAME: &'static str = "sha1";
+    const BIG_ENDIAN: bool = true;
+
+    type State = [u32; 5];
+    const INIT: Self::State = S0;
+
+    #[inline]
+    fn compress(state: &mut Self::State, block: &[u8; BLOCK_SIZE]) {
+        compress(state, &be_words(block));
+    }
+
+    fn output(state: &Self::State) -> Vec<u8> {
+        let mut out = vec![0; 20];
+        write_words(state, &mut out, true);
+        out
+    }
+
+    #[inline]
+    fn output_prefix(state: &Self::State) -> [u8; 16] {
+        let mut out = [0_u8; 16];
+        write_words(state, &mut out, true);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sha1;
+    use crate::block_hash::digest;
+    use crate::printer::hex;
+
+    #[test]
+    fn test_vectors() {
+        let cases: [(&[u8], &str); 3] = [
+            (b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            (b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(hex(&digest::<Sha1>(data)), expected);
+        }
+    }
+}
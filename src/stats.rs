@@ -27,7 +27,7 @@ impl AtomicStats {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Stats {
     pub trails: u64,
     pub hashes: u64,
@@ -0,0 +1,208 @@
+use crate::HashAdapter;
+
+/// A trail, described by its starting point and the number of steps it
+/// takes to reach its distinguishing end point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrailInfo<P> {
+    pub start: P,
+    pub length: u64,
+}
+
+/// Outcome of tracing two trails which end in the same point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceResult<P> {
+    /// The trails merge after points `(a, b)` with different bifurcations,
+    /// `a` is the point with the bifurcation `true`.
+    GoodCollision(P, P),
+    /// The trails merge after points `(a, b)` with the same bifurcation, so
+    /// both points went through the same function.
+    SelfCollision(P, P),
+    /// One trail starts on the other one, the point is where they join.
+    RobinHood(P),
+    /// The trails do not merge within their lengths, so at least one of
+    /// them does not end where it was recorded to.
+    NotFound,
+}
+
+/// Find where the trails `a` and `b`, which end in the same point, merge.
+pub fn trace_collision<A: HashAdapter>(
+    adapter: &A,
+    a: &TrailInfo<A::Point>,
+    b: &TrailInfo<A::Point>,
+) -> TraceResult<A::Point> {
+    let TrailInfo {
+        start: mut a,
+        length: mut a_len,
+    } = a;
+    let TrailInfo {
+        start: mut b,
+        length: mut b_len,
+    } = b;
+
+    while b_len > a_len {
+        let bifurcation = adapter.bifurcation(b);
+        b = adapter.next_point(b, bifurcation);
+        b_len -= 1;
+    }
+
+    while a_len > b_len {
+        let bifurcation = adapter.bifurcation(a);
+        a = adapter.next_point(a, bifurcation);
+        a_len -= 1;
+    }
+
+    if a == b {
+        return TraceResult::RobinHood(a);
+    }
+
+    for _ in 0..a_len {
+        let a_bifurcation = adapter.bifurcation(a);
+        let next_a = adapter.next_point(a, a_bifurcation);
+        let b_bifurcation = adapter.bifurcation(b);
+        let next_b = adapter.next_point(b, b_bifurcation);
+        if next_a == next_b {
+            return match (a_bifurcation, b_bifurcation) {
+                (false, false) | (true, true) => TraceResult::SelfCollision(a, b),
+                (true, false) => TraceResult::GoodCollision(a, b),
+                (false, true) => TraceResult::GoodCollision(b, a),
+            };
+        }
+
+        a = next_a;
+        b = next_b;
+    }
+
+    TraceResult::NotFound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{trace_collision, TraceResult, TrailInfo};
+    use crate::HashAdapter;
+    use core::ops::ControlFlow;
+    use rand::Rng;
+
+    /// Points `0..16` moved by two fixed permutations, picked by the
+    /// bifurcation, except for the few points in `merges`, which are sent
+    /// to a chosen point instead.  Point `0` is the only distinguishing one.
+    struct Toy {
+        bifurcations: u16,
+        merges: &'static [(u8, u8)],
+    }
+
+    const PERM_TRUE: [u8; 16] = [0, 5, 9, 14, 2, 11, 7, 1, 12, 4, 15, 3, 8, 6, 10, 13];
+    const PERM_FALSE: [u8; 16] = [0, 12, 3, 7, 10, 1, 14, 9, 5, 13, 2, 8, 15, 11, 4, 6];
+
+    impl HashAdapter for Toy {
+        type Point = u8;
+
+        fn trail_limit(&self) -> u64 {
+            16
+        }
+
+        fn make_point<R: Rng>(&self, rng: &mut R) -> Self::Point {
+            rng.gen_range(0..16)
+        }
+
+        fn is_distinguishing(&self, x: Self::Point) -> bool {
+            x == 0
+        }
+
+        fn bifurcation(&self, x: Self::Point) -> bool {
+            self.bifurcations & (1 << x) != 0
+        }
+
+        fn next_point(&self, x: Self::Point, bi: bool) -> Self::Point {
+            if let Some(&(_, to)) = self.merges.iter().find(|&&(from, _)| from == x) {
+                return to;
+            }
+            if bi {
+                PERM_TRUE[x as usize]
+            } else {
+                PERM_FALSE[x as usize]
+            }
+        }
+
+        fn report_collision(&self, _a: Self::Point, _b: Self::Point) -> ControlFlow<(), ()> {
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn trail(start: u8, length: u64) -> TrailInfo<u8> {
+        TrailInfo { start, length }
+    }
+
+    #[test]
+    fn good_collision() {
+        // 1 -(true)-> 5 and 2 -(false)-> 3 -> 5, both reach 0 from 5.
+        let toy = Toy {
+            bifurcations: 1 << 1,
+            merges: &[(1, 5), (2, 3), (3, 5), (5, 0)],
+        };
+        assert_eq!(
+            trace_collision(&toy, &trail(1, 2), &trail(2, 3)),
+            TraceResult::GoodCollision(1, 3)
+        );
+        // The point with the `true` bifurcation always comes first.
+        assert_eq!(
+            trace_collision(&toy, &trail(2, 3), &trail(1, 2)),
+            TraceResult::GoodCollision(1, 3)
+        );
+    }
+
+    #[test]
+    fn self_collision() {
+        let toy = Toy {
+            bifurcations: 0,
+            merges: &[(1, 5), (2, 5), (5, 0)],
+        };
+        assert_eq!(
+            trace_collision(&toy, &trail(1, 2), &trail(2, 2)),
+            TraceResult::SelfCollision(1, 2)
+        );
+    }
+
+    #[test]
+    fn robin_hood() {
+        // 4 -> 2 -> 3 -> 0, the second trail starts on the first one.
+        let toy = Toy {
+            bifurcations: 0,
+            merges: &[(4, 2), (2, 3), (3, 0)],
+        };
+        assert_eq!(
+            trace_collision(&toy, &trail(4, 3), &trail(2, 2)),
+            TraceResult::RobinHood(2)
+        );
+        assert_eq!(
+            trace_collision(&toy, &trail(2, 2), &trail(4, 3)),
+            TraceResult::RobinHood(2)
+        );
+        assert_eq!(
+            trace_collision(&toy, &trail(4, 3), &trail(4, 3)),
+            TraceResult::RobinHood(4)
+        );
+    }
+
+    #[test]
+    fn not_found() {
+        // 1 -> 0 and 2 -> 3 -> 0 never meet before the end when the
+        // recorded lengths are wrong.
+        let toy = Toy {
+            bifurcations: 0,
+            merges: &[(1, 0), (2, 3), (3, 0)],
+        };
+        assert_eq!(
+            trace_collision(&toy, &trail(1, 1), &trail(2, 1)),
+            TraceResult::NotFound
+        );
+        // A single permutation never merges two different points.
+        let toy = Toy {
+            bifurcations: 0xffff,
+            merges: &[],
+        };
+        assert_eq!(
+            trace_collision(&toy, &trail(7, 4), &trail(9, 4)),
+            TraceResult::NotFound
+        );
+    }
+}